@@ -0,0 +1,58 @@
+//! Times parsing and writing a statement with many rows, run with
+//! `cargo bench`.
+use criterion::{criterion_group, criterion_main, Criterion};
+use fs_ledger::{Config, Ledger};
+use std::fs;
+use std::hint::black_box;
+use std::process::Command;
+
+const ROWS: usize = 10_000;
+
+/// Repeats the rows of the fixture statement until it has `ROWS` rows.
+fn statement() -> String {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let start = src.find("2020-01-01").unwrap();
+    let end = src.find("Important!").unwrap();
+    let rows = &src[start..end];
+    let mut big = src[..start].to_owned();
+    for _ in 0..ROWS / rows.matches("2020-").count() {
+        big.push_str(rows);
+    }
+    big.push_str(&src[end..]);
+    big
+}
+
+fn parse(c: &mut Criterion) {
+    let src = statement();
+    let config = Config::default();
+    c.bench_function("parse_statement", |b| {
+        b.iter(|| fs_ledger::parse_statement(black_box(&src), &config).unwrap())
+    });
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    c.bench_function("entries", |b| {
+        b.iter(|| fs_ledger::entries(black_box(&statement.transactions), &config).unwrap())
+    });
+    let entries = fs_ledger::entries(&statement.transactions, &config).unwrap();
+    c.bench_function("write_entries", |b| {
+        b.iter(|| {
+            let mut buf = Vec::new();
+            fs_ledger::write_entries(&mut buf, &entries, &mut Ledger, &config).unwrap();
+            buf
+        })
+    });
+}
+
+/// Times pdftotext on the fixture pdf, skipped if it is not installed.
+fn pdf(c: &mut Criterion) {
+    if Command::new("pdftotext").arg("-v").output().is_err() {
+        eprintln!("pdftotext not found, skipping pdf_to_text");
+        return;
+    }
+    let path = "tests/fixtures/statement.pdf";
+    c.bench_function("pdf_to_text", |b| {
+        b.iter(|| fs_ledger::pdf_to_text(black_box(path)).unwrap())
+    });
+}
+
+criterion_group!(benches, parse, pdf);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+//! Amounts in the statement, like `1,000.00`.
+use crate::{LedgerError, Result};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// How negative amounts are written by [`Config::amount`](crate::Config::amount).
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Negative {
+    /// `-1.00`
+    #[default]
+    Minus,
+    /// `(1.00)`, ledger reads this as an expression so it is only for reading.
+    Paren,
+}
+
+impl FromStr for Negative {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minus" => Ok(Negative::Minus),
+            "paren" => Ok(Negative::Paren),
+            _ => Err(format!("invalid negative {:?}, expected minus or paren", s)),
+        }
+    }
+}
+
+/// Parses an amount with either `.` or `,` before the cents, the other one
+/// separates thousands. The last separator is taken as thousands if three
+/// digits follow it, like `1,000`.
+pub fn parse(s: &str) -> Result<Decimal> {
+    let decimal = s.rfind([',', '.']).filter(|i| s.len() - i != 4);
+    let mut digits = String::with_capacity(s.len());
+    for (i, c) in s.char_indices() {
+        match c {
+            ',' | '.' if Some(i) == decimal => digits.push('.'),
+            ',' | '.' => {}
+            c => digits.push(c),
+        }
+    }
+    digits
+        .parse()
+        .map_err(|_| LedgerError::InvalidAmount(s.to_owned()))
+}
+
+/// Parses an amount written with the given separators, like [`format_in`].
+pub fn parse_in(s: &str, decimal: &str, thousands: &str) -> Result<Decimal> {
+    let digits = match thousands {
+        "" => s.to_owned(),
+        sep => s.replace(sep, ""),
+    };
+    digits
+        .replace(decimal, ".")
+        .parse()
+        .map_err(|_| LedgerError::InvalidAmount(s.to_owned()))
+}
+
+/// Formats an amount with two decimals and thousands separators.
+pub fn format(amount: Decimal) -> String {
+    format_in(amount, ".", ",")
+}
+
+/// Formats an amount like [`format`] with the given separators, an empty
+/// `thousands` leaves them out.
+pub fn format_in(amount: Decimal, decimal: &str, thousands: &str) -> String {
+    let digits = format!("{:.2}", amount.abs());
+    let (int, frac) = digits.split_once('.').unwrap_or((&digits, ""));
+    let mut s = String::with_capacity(digits.len() + int.len() / 3 * thousands.len() + 1);
+    if amount.is_sign_negative() && !amount.is_zero() {
+        s.push('-');
+    }
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            s.push_str(thousands);
+        }
+        s.push(c);
+    }
+    s.push_str(decimal);
+    s.push_str(frac);
+    s
+}
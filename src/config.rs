@@ -0,0 +1,372 @@
+use crate::amount::{self, Negative};
+use crate::{DateOrder, Result};
+use regex::Regex;
+use rust_decimal::Decimal;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+// accounts
+const ASSET: &str = "assets:fundingsocieties";
+const FUNDS: &str = "assets:funds:fundingsocieties";
+const BANK: &str = "assets:bank:pbe";
+const INCOME: &str = "income:interest";
+const EXPENSE: &str = "expenses:service";
+const TAX: &str = "expenses:tax";
+const UNKNOWN: &str = "expenses:unknown";
+const EQUITY: &str = "equity:opening balances";
+const BONUS: &str = "income:bonus";
+const REFERRAL: &str = "income:referral";
+const WITHHOLDING: &str = "expenses:tax:withholding";
+
+const COMMODITY: &str = "MYR";
+const LINE_WIDTH: usize = 62;
+
+/// Accounts and commodity used in the ledger, loaded with `--config`.
+///
+/// Missing keys fall back to the constants above, except `commodity` which is
+/// taken from the statement.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub asset: String,
+    pub funds: String,
+    pub bank: String,
+    /// Accounts by bank name in titles like `Withdrawal to Maybank`, the
+    /// others use `bank`.
+    pub banks: BTreeMap<String, String>,
+    pub income: String,
+    pub expense: String,
+    /// Account of the tax in service fees and `SST` rows.
+    pub tax: String,
+    /// Percentage of tax included in service fees, which are split into the
+    /// fee and the tax, also `--sst-rate`.
+    pub sst_rate: Option<Decimal>,
+    /// Accounts by row comment like `Bonus`, these take precedence over the
+    /// built-in comments.
+    pub comments: BTreeMap<String, String>,
+    /// Rules tried in order before `comments`, also loaded with `--rules`.
+    pub rules: Vec<Rule>,
+    /// Account for rows with unknown comment.
+    pub unknown: String,
+    /// Account the opening balance comes from.
+    pub equity: String,
+    /// Beancount names of accounts and their subaccounts, like
+    /// `Assets:FundingSocieties` for `assets:fundingsocieties`, others are
+    /// capitalized.
+    pub beancount_accounts: BTreeMap<String, String>,
+    /// Commodity of statements without a currency in the header, `MYR` if not
+    /// given, `--commodity` overrides the statements too.
+    pub commodity: Option<String>,
+    pub indent: Indent,
+    pub width: usize,
+    /// Start of posting and entry comments.
+    pub comment_prefix: String,
+    /// Spaces before a comment.
+    pub comment_gap: usize,
+    /// Negative amounts as `minus` or `paren`, also `--negative`.
+    pub negative: Negative,
+    /// Separator before the cents, also `--decimal-sep`.
+    pub decimal_sep: String,
+    /// Separator of thousands, empty for none, also `--thousands-sep`.
+    pub thousands_sep: String,
+    /// Order of ambiguous dates like `03/04/2024`, either `dmy` or `mdy`.
+    pub date_order: DateOrder,
+    /// Mark entries pending with `!` instead of cleared with `*`, also
+    /// `--all-pending` and `--all-cleared`.
+    pub pending: bool,
+    /// Flag of all entries, overrides `pending`, also `--flag`.
+    pub flag: Option<Flag>,
+    /// Assert the balance before the first entry, also `--opening-balance`.
+    pub opening_balance: bool,
+    /// Declare the commodity before the entries, also `--declare-commodity`.
+    pub declare_commodity: bool,
+    /// Running balance of the statement after each posting comment, also
+    /// `--inline-balance`.
+    pub inline_balance: bool,
+    /// Tag entries with their loan id, also `--loan-tag`.
+    pub loan_tag: bool,
+    /// Post to a subaccount of `funds` for each loan, also `--per-loan`.
+    pub per_loan: bool,
+    /// Write a comment when the page of the statement changes, also
+    /// `--page-comments`.
+    pub page_comments: bool,
+    /// Keep going when pdftotext reports errors, also `--allow-errors`.
+    pub allow_errors: bool,
+    /// Fail on unknown rows instead of posting them to `unknown`.
+    pub strict: bool,
+}
+
+/// Posts rows whose comment, and title if given, match to `account`.
+#[derive(Clone, Deserialize)]
+pub struct Rule {
+    #[serde(deserialize_with = "regex")]
+    pub comment: Regex,
+    #[serde(default, deserialize_with = "optional_regex")]
+    pub title: Option<Regex>,
+    pub account: String,
+}
+
+impl Rule {
+    fn is_match(&self, title: &str, comment: &str) -> bool {
+        self.comment.is_match(comment) && self.title.as_ref().is_none_or(|re| re.is_match(title))
+    }
+}
+
+fn regex<'de, D: Deserializer<'de>>(d: D) -> Result<Regex, D::Error> {
+    let s = String::deserialize(d)?;
+    Regex::new(&s).map_err(de::Error::custom)
+}
+
+fn optional_regex<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Regex>, D::Error> {
+    regex(d).map(Some)
+}
+
+/// Rules file given with `--rules`.
+#[derive(Deserialize)]
+struct Rules {
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+/// Indentation of postings, either `tab` or a number of spaces.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Indent {
+    Tab,
+    Spaces(usize),
+}
+
+impl Indent {
+    /// Columns taken by the indentation, a tab counts as 8.
+    pub fn width(self) -> usize {
+        match self {
+            Indent::Tab => 8,
+            Indent::Spaces(n) => n,
+        }
+    }
+}
+
+impl fmt::Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Indent::Tab => f.write_str("\t"),
+            Indent::Spaces(n) => write!(f, "{:n$}", "", n = n),
+        }
+    }
+}
+
+impl FromStr for Indent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tab" => Ok(Indent::Tab),
+            _ => s
+                .parse()
+                .map(Indent::Spaces)
+                .map_err(|_| format!("invalid indent {:?}, expected tab or a number", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Indent {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Flag between the date and payee, `*`, `!` or `none`.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Flag {
+    Cleared,
+    Pending,
+    None,
+}
+
+impl Flag {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Flag::Cleared => "*",
+            Flag::Pending => "!",
+            Flag::None => "",
+        }
+    }
+}
+
+impl FromStr for Flag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "*" => Ok(Flag::Cleared),
+            "!" => Ok(Flag::Pending),
+            "none" => Ok(Flag::None),
+            _ => Err(format!("invalid flag {:?}, expected *, ! or none", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Flag {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            asset: ASSET.to_owned(),
+            funds: FUNDS.to_owned(),
+            bank: BANK.to_owned(),
+            banks: BTreeMap::new(),
+            income: INCOME.to_owned(),
+            expense: EXPENSE.to_owned(),
+            tax: TAX.to_owned(),
+            sst_rate: None,
+            comments: BTreeMap::new(),
+            rules: Vec::new(),
+            unknown: UNKNOWN.to_owned(),
+            equity: EQUITY.to_owned(),
+            beancount_accounts: BTreeMap::new(),
+            commodity: None,
+            indent: Indent::Tab,
+            width: LINE_WIDTH,
+            comment_prefix: "; ".to_owned(),
+            comment_gap: 2,
+            negative: Negative::Minus,
+            decimal_sep: ".".to_owned(),
+            thousands_sep: ",".to_owned(),
+            date_order: DateOrder::DayMonth,
+            pending: false,
+            flag: None,
+            opening_balance: false,
+            declare_commodity: false,
+            inline_balance: false,
+            loan_tag: false,
+            per_loan: false,
+            page_comments: false,
+            allow_errors: false,
+            strict: false,
+        }
+    }
+}
+
+impl Config {
+    /// Reads config from a toml file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let src = fs::read_to_string(path)?;
+        Ok(toml::from_str(&src)?)
+    }
+
+    /// Reads `[[rules]]` from a toml file, they are tried before the others.
+    pub fn load_rules(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let src = fs::read_to_string(path)?;
+        let rules: Rules = toml::from_str(&src)?;
+        self.rules.splice(0..0, rules.rules);
+        Ok(())
+    }
+
+    /// Flag of the entries, `!` if pending and `*` if cleared unless `flag` is
+    /// given, empty for none.
+    pub fn flag(&self) -> &'static str {
+        match self.flag {
+            Some(flag) => flag.as_str(),
+            None if self.pending => "!",
+            None => "*",
+        }
+    }
+
+    /// Formats an amount with the separators, negatives as `negative`.
+    pub fn amount(&self, amount: Decimal) -> String {
+        match self.negative {
+            Negative::Paren if amount.is_sign_negative() && !amount.is_zero() => {
+                format!("({})", self.assertion(-amount))
+            }
+            _ => self.assertion(amount),
+        }
+    }
+
+    /// Formats an amount with the separators and a minus for negatives, as
+    /// ledger reads `(1.00)` in balance assertions as positive.
+    pub fn assertion(&self, amount: Decimal) -> String {
+        amount::format_in(amount, &self.decimal_sep, &self.thousands_sep)
+    }
+
+    pub fn commodity(&self) -> &str {
+        self.commodity.as_deref().unwrap_or(COMMODITY)
+    }
+
+    /// Bank account of a deposit or withdrawal, by the bank after `to` or `from`.
+    pub fn bank(&self, title: &str) -> &str {
+        let name = title
+            .split_once(" to ")
+            .or_else(|| title.split_once(" from "))
+            .map(|(_, name)| name.trim());
+        name.and_then(|name| {
+            self.banks
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        })
+        .map_or(&self.bank, |(_, acc)| acc)
+    }
+
+    /// Account of a repayment row by its comment, `None` if unknown.
+    pub fn account(&self, title: &str, comment: &str) -> Option<&str> {
+        let rule = self.rules.iter().find(|rule| rule.is_match(title, comment));
+        if let Some(rule) = rule {
+            return Some(&rule.account);
+        }
+        let custom = self
+            .comments
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(comment));
+        if let Some((_, acc)) = custom {
+            return Some(acc);
+        }
+        match comment {
+            "Service Fee" => Some(&self.expense),
+            "SST" | "Service Tax" => Some(&self.tax),
+            "Interest" | "Early Payment Fee" | "Late Interest Fee" | "Returns"
+            | "Late Returns Fee" => Some(&self.income),
+            "Principal" => Some(&self.funds),
+            "Bonus" | "Cashback" => Some(BONUS),
+            "Referral" => Some(REFERRAL),
+            "Withholding Tax" => Some(WITHHOLDING),
+            _ => None,
+        }
+    }
+
+    /// Smallest width that fits the longest account with a small amount.
+    pub fn min_width(&self) -> usize {
+        let accounts = [
+            &self.asset,
+            &self.funds,
+            &self.bank,
+            &self.income,
+            &self.expense,
+            &self.tax,
+            &self.unknown,
+        ];
+        let builtin = [BONUS, REFERRAL, WITHHOLDING];
+        let longest = accounts
+            .into_iter()
+            .chain(self.banks.values())
+            .chain(self.comments.values())
+            .chain(self.rules.iter().map(|rule| &rule.account))
+            .map(|acc| acc.len())
+            .chain(builtin.map(str::len))
+            .max()
+            .unwrap_or(0);
+        self.indent.width() + longest + " -0.00".len() + 1
+    }
+}
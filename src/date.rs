@@ -0,0 +1,58 @@
+use crate::{LedgerError, Result};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Pattern matching any date accepted by [`parse`], for use in `concat!`.
+macro_rules! date_pattern {
+    () => {
+        r"(?:[0-9]{4}[-/][0-9]{2}[-/][0-9]{2}|[0-9]{1,2}-[A-Za-z]{3}-[0-9]{4}|[0-9]{1,2}/[0-9]{1,2}/[0-9]{4})"
+    };
+}
+
+/// Date formats accepted in the statement, tried in order. `%d %b %Y` is only
+/// in the statement period.
+const FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%d-%b-%Y", "%d %b %Y"];
+
+/// Order of day and month in dates like `03/04/2024`.
+#[derive(Clone, Copy, Default, Deserialize)]
+pub enum DateOrder {
+    /// `03/04/2024` is 3 April.
+    #[default]
+    #[serde(rename = "dmy")]
+    DayMonth,
+    /// `03/04/2024` is 4 March.
+    #[serde(rename = "mdy")]
+    MonthDay,
+}
+
+impl DateOrder {
+    /// Formats with slashes, the ambiguous one first.
+    fn formats(self) -> [&'static str; 2] {
+        match self {
+            DateOrder::DayMonth => ["%d/%m/%Y", "%m/%d/%Y"],
+            DateOrder::MonthDay => ["%m/%d/%Y", "%d/%m/%Y"],
+        }
+    }
+}
+
+impl FromStr for DateOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dmy" => Ok(DateOrder::DayMonth),
+            "mdy" => Ok(DateOrder::MonthDay),
+            _ => Err(format!("invalid date order {:?}, expected dmy or mdy", s)),
+        }
+    }
+}
+
+/// Parses a date from the statement.
+pub fn parse(s: &str, order: DateOrder) -> Result<NaiveDate> {
+    FORMATS
+        .iter()
+        .chain(&order.formats())
+        .find_map(|fmt| NaiveDate::parse_from_str(s, fmt).ok())
+        .ok_or_else(|| LedgerError::InvalidDate(s.to_owned()))
+}
@@ -0,0 +1,82 @@
+use crate::Transaction;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use thiserror::Error;
+
+/// Errors from reading statements and writing ledger.
+#[derive(Debug, Error)]
+pub enum LedgerError {
+    /// The statement does not exist.
+    #[error("input file not found")]
+    NotFound,
+    /// pdftotext could not be run or failed on the pdf.
+    #[error("cannot load pdf: {0}")]
+    PdfLoad(String),
+    /// pdftotext reported errors while extracting the text.
+    #[error("{}", extraction(.0))]
+    Extraction(Vec<String>),
+    /// A marker around the transaction table is missing.
+    #[error("cannot find {0}")]
+    MissingMarker(&'static str),
+    /// Text in the table that is not a row, `row` counts from 1.
+    #[error("malformed row {row} near {near:?}")]
+    MalformedRow { row: usize, near: String },
+    /// A line in a ledger which was not written by this crate, `line` counts
+    /// from 1.
+    #[error("malformed ledger line {line} near {near:?}")]
+    MalformedLedger { line: usize, near: String },
+    /// A date in none of the known formats.
+    #[error("invalid date {0:?}")]
+    InvalidDate(String),
+    /// An amount which is not a number.
+    #[error("invalid amount {0:?}")]
+    InvalidAmount(String),
+    /// A row which cannot be classified into postings.
+    #[error(
+        "unknown transaction {} {:?} with comment {:?}, debit {} credit {}",
+        .0.date, .0.title, .0.comment, .0.debit, .0.credit
+    )]
+    UnknownTransaction(Box<Transaction>),
+    /// Rows posted to the unknown account during `--dry-run`.
+    #[error("{0} rows with unknown comment")]
+    UnknownComments(usize),
+    /// Rows found by `--verify` which do not match the running balance.
+    #[error("{0} rows do not match the running balance")]
+    BalanceMismatch(usize),
+    /// hledger could not read the output of `--validate`.
+    #[error("hledger rejected the output: {0}")]
+    Validation(String),
+    /// Config file cannot be parsed.
+    #[error("invalid config: {0}")]
+    Config(#[from] toml::de::Error),
+    /// An error in a statement file.
+    #[error("{}: {source}", path.display())]
+    Statement {
+        path: PathBuf,
+        source: Box<LedgerError>,
+    },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Number of errors reported by pdftotext with the first one.
+fn extraction(errors: &[String]) -> String {
+    match errors.first() {
+        Some(first) => format!(
+            "pdftotext reported {} errors, first {:?}",
+            errors.len(),
+            first
+        ),
+        None => "pdftotext reported errors".to_owned(),
+    }
+}
+
+impl LedgerError {
+    pub(crate) fn pdftotext(status: ExitStatus, stderr: &[u8]) -> Self {
+        let stderr = String::from_utf8_lossy(stderr);
+        LedgerError::PdfLoad(format!("pdftotext {}: {}", status, stderr.trim()))
+    }
+}
+
+pub type Result<T, E = LedgerError> = std::result::Result<T, E>;
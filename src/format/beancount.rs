@@ -0,0 +1,182 @@
+use super::{commodities, posting, Format};
+use crate::{Config, Entry, Posting};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::io::{self, Write};
+
+/// Beancount, accounts are renamed by [`Config::beancount_accounts`] or
+/// capitalized and opened before the first entry.
+pub struct Beancount;
+
+/// Capitalizes an account component, words are joined in camel case since
+/// beancount does not allow spaces, `service fee` becomes `ServiceFee`.
+fn component(s: &str) -> String {
+    s.split(|c: char| !c.is_alphanumeric() && c != '-')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars))
+        })
+        .flatten()
+        .collect()
+}
+
+/// Converts a ledger account name by `beancount_accounts`, the longest
+/// account given there which is the account or one of its parents is
+/// replaced and the rest is capitalized by component.
+///
+/// Without a mapping `assets:fundingsocieties` becomes
+/// `Assets:Fundingsocieties`, since words run together are not split.
+fn account(acc: &str, config: &Config) -> String {
+    let mapped = config
+        .beancount_accounts
+        .iter()
+        .filter(|(from, _)| {
+            acc.strip_prefix(from.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+        })
+        .max_by_key(|(from, _)| from.len());
+    let (mut name, rest) = match mapped {
+        Some((from, to)) => (to.clone(), &acc[from.len()..]),
+        None => (String::new(), acc),
+    };
+    for part in rest.split(':').filter(|part| !part.is_empty()) {
+        if !name.is_empty() {
+            name.push(':');
+        }
+        name.push_str(&component(part));
+    }
+    name
+}
+
+/// Quotes a beancount string.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Format for Beancount {
+    fn prelude(
+        &mut self,
+        buf: &mut dyn Write,
+        entries: &[Entry],
+        config: &Config,
+    ) -> io::Result<()> {
+        let date = match entries.iter().map(|entry| entry.date).min() {
+            Some(date) => date,
+            None => return Ok(()),
+        };
+        // opened before the pad of the opening balance
+        let date = match config.opening_balance {
+            true => date.pred_opt().unwrap_or(date),
+            false => date,
+        };
+        if config.declare_commodity {
+            for commodity in commodities(entries, config) {
+                writeln!(buf, "{} commodity {}", date, commodity)?;
+            }
+        }
+        let mut accounts = vec![&config.asset[..]];
+        if config.opening_balance {
+            accounts.push(&config.equity);
+        }
+        for posting in entries.iter().flat_map(|entry| &entry.postings) {
+            if !accounts.contains(&&*posting.account) {
+                accounts.push(&posting.account);
+            }
+        }
+        for acc in accounts {
+            writeln!(buf, "{} open {}", date, account(acc, config))?;
+        }
+        writeln!(buf)
+    }
+
+    fn opening(
+        &mut self,
+        buf: &mut dyn Write,
+        date: NaiveDate,
+        balance: Decimal,
+        commodity: &str,
+        config: &Config,
+    ) -> io::Result<()> {
+        // balance is checked at the start of the day, pad fills it the day before
+        let asset = account(&config.asset, config);
+        let pad_date = date.pred_opt().unwrap_or(date);
+        writeln!(
+            buf,
+            "{} pad {} {}",
+            pad_date,
+            asset,
+            account(&config.equity, config)
+        )?;
+        writeln!(
+            buf,
+            "{} balance {} {:.2} {}\n",
+            date, asset, balance, commodity
+        )
+    }
+
+    fn closing(
+        &mut self,
+        buf: &mut dyn Write,
+        date: NaiveDate,
+        balance: Decimal,
+        commodity: &str,
+        config: &Config,
+    ) -> io::Result<()> {
+        // balance is checked at the start of the day
+        writeln!(
+            buf,
+            "{} balance {} {:.2} {}\n",
+            date.succ_opt().unwrap_or(date),
+            account(&config.asset, config),
+            balance,
+            commodity
+        )
+    }
+
+    fn comment(&mut self, buf: &mut dyn Write, text: &str, config: &Config) -> io::Result<()> {
+        for line in text.lines() {
+            writeln!(buf, "{}{}", config.comment_prefix, line)?;
+        }
+        writeln!(buf)
+    }
+
+    fn header(&mut self, buf: &mut dyn Write, entry: &Entry, config: &Config) -> io::Result<()> {
+        // entries without a comment like investments are narrated by their posting
+        let narration = match entry.postings.first() {
+            Some(p) if entry.comment.is_empty() => p.comment,
+            _ => entry.comment,
+        };
+        writeln!(
+            buf,
+            "{} {} {} {}",
+            entry.date,
+            // beancount needs a flag or `txn`
+            match config.flag() {
+                "" => "txn",
+                flag => flag,
+            },
+            quote(entry.payee),
+            quote(narration)
+        )?;
+        match entry.loan {
+            Some(loan) if config.loan_tag => {
+                writeln!(buf, "{}loan: {}", config.indent, quote(loan))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn balance(&mut self, buf: &mut dyn Write, _entry: &Entry, config: &Config) -> io::Result<()> {
+        writeln!(buf, "{}{}", config.indent, account(&config.asset, config))
+    }
+
+    fn pay(
+        &mut self,
+        buf: &mut dyn Write,
+        entry: &Entry,
+        p: &Posting,
+        config: &Config,
+    ) -> io::Result<()> {
+        posting(buf, config, &account(&p.account, config), entry, p)
+    }
+}
@@ -0,0 +1,67 @@
+use super::Format;
+use crate::{Config, Entry, Posting};
+use std::io::{self, Write};
+
+/// Comma separated values with one row per posting.
+///
+/// The statement account is left out since its amount is the balance of the
+/// other postings, the running total column gives its balance instead.
+/// Thousands separators are dropped so spreadsheets read amounts as numbers.
+pub struct Csv;
+
+/// Quotes a field if needed.
+fn field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+impl Format for Csv {
+    fn prelude(
+        &mut self,
+        buf: &mut dyn Write,
+        _entries: &[Entry],
+        _config: &Config,
+    ) -> io::Result<()> {
+        writeln!(buf, "date,payee,comment,account,amount,commodity,total")
+    }
+
+    fn header(&mut self, _buf: &mut dyn Write, _entry: &Entry, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn balance(
+        &mut self,
+        _buf: &mut dyn Write,
+        _entry: &Entry,
+        _config: &Config,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pay(
+        &mut self,
+        buf: &mut dyn Write,
+        entry: &Entry,
+        p: &Posting,
+        _config: &Config,
+    ) -> io::Result<()> {
+        writeln!(
+            buf,
+            "{},{},{},{},{:.2},{},{:.2}",
+            entry.date,
+            field(entry.payee),
+            field(p.comment),
+            field(&p.account),
+            p.amount,
+            field(entry.commodity),
+            p.txn.total,
+        )
+    }
+
+    fn end(&mut self, _buf: &mut dyn Write, _entry: &Entry, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use super::Format;
+use crate::{Config, Entry, Posting};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// Array of parsed rows, each with the account it was classified to.
+///
+/// Every row is written once, also those with nothing to post or split into
+/// several postings. Amounts are strings with two decimals so they are read
+/// exactly.
+#[derive(Default)]
+pub struct Json {
+    rows: usize,
+}
+
+#[derive(Serialize)]
+struct Row<'a> {
+    date: NaiveDate,
+    title: &'a str,
+    comment: &'a str,
+    debit: String,
+    credit: String,
+    total: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<usize>,
+    account: &'a str,
+    commodity: &'a str,
+}
+
+impl Format for Json {
+    fn prelude(
+        &mut self,
+        buf: &mut dyn Write,
+        _entries: &[Entry],
+        _config: &Config,
+    ) -> io::Result<()> {
+        write!(buf, "[")
+    }
+
+    fn header(&mut self, _buf: &mut dyn Write, _entry: &Entry, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn balance(
+        &mut self,
+        _buf: &mut dyn Write,
+        _entry: &Entry,
+        _config: &Config,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pay(
+        &mut self,
+        _buf: &mut dyn Write,
+        _entry: &Entry,
+        _p: &Posting,
+        _config: &Config,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn end(&mut self, buf: &mut dyn Write, entry: &Entry, _config: &Config) -> io::Result<()> {
+        for row in &entry.rows {
+            let txn = row.txn;
+            let row = Row {
+                date: txn.date,
+                title: &txn.title,
+                comment: &txn.comment,
+                debit: format!("{:.2}", txn.debit),
+                credit: format!("{:.2}", txn.credit),
+                total: format!("{:.2}", txn.total),
+                page: txn.page,
+                account: &row.account,
+                commodity: entry.commodity,
+            };
+            write!(buf, "{}\n  ", if self.rows == 0 { "" } else { "," })?;
+            serde_json::to_writer(&mut *buf, &row)?;
+            self.rows += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, buf: &mut dyn Write, _config: &Config) -> io::Result<()> {
+        writeln!(buf, "{}]", if self.rows == 0 { "" } else { "\n" })
+    }
+}
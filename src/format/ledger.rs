@@ -0,0 +1,116 @@
+use super::{commodities, pad, posting, Format};
+use crate::{Config, Entry, Posting};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::io::{self, Write};
+
+/// Plain text ledger, which is the default.
+pub struct Ledger;
+
+/// Flag with the space before it, nothing without a flag.
+fn flag(config: &Config) -> String {
+    match config.flag() {
+        "" => String::new(),
+        flag => format!(" {}", flag),
+    }
+}
+
+impl Format for Ledger {
+    fn prelude(
+        &mut self,
+        buf: &mut dyn Write,
+        entries: &[Entry],
+        config: &Config,
+    ) -> io::Result<()> {
+        if config.declare_commodity {
+            for commodity in commodities(entries, config) {
+                writeln!(buf, "commodity {}", commodity)?;
+            }
+            writeln!(buf)?;
+        }
+        Ok(())
+    }
+
+    fn opening(
+        &mut self,
+        buf: &mut dyn Write,
+        date: NaiveDate,
+        balance: Decimal,
+        commodity: &str,
+        config: &Config,
+    ) -> io::Result<()> {
+        let amt = config.assertion(balance);
+        writeln!(buf, "{}{} Opening Balance", date, flag(config))?;
+        writeln!(
+            buf,
+            "{}{}{:pad$} = {} {}",
+            config.indent,
+            config.asset,
+            "",
+            amt,
+            commodity,
+            pad = pad(config, &config.asset, "= ".len() + amt.len())
+        )?;
+        writeln!(buf, "{}{}\n", config.indent, config.equity)
+    }
+
+    fn closing(
+        &mut self,
+        buf: &mut dyn Write,
+        date: NaiveDate,
+        balance: Decimal,
+        commodity: &str,
+        config: &Config,
+    ) -> io::Result<()> {
+        let amt = format!("0 {} = {}", commodity, config.assertion(balance));
+        writeln!(buf, "{}{} Closing Balance", date, flag(config))?;
+        writeln!(
+            buf,
+            "{}{}{:pad$} {} {}\n",
+            config.indent,
+            config.asset,
+            "",
+            amt,
+            commodity,
+            pad = pad(config, &config.asset, amt.len())
+        )
+    }
+
+    fn comment(&mut self, buf: &mut dyn Write, text: &str, config: &Config) -> io::Result<()> {
+        for line in text.lines() {
+            writeln!(buf, "{}{}", config.comment_prefix, line)?;
+        }
+        writeln!(buf)
+    }
+
+    fn header(&mut self, buf: &mut dyn Write, entry: &Entry, config: &Config) -> io::Result<()> {
+        write!(buf, "{}{} {}", entry.date, flag(config), entry.payee)?;
+        if !entry.comment.is_empty() {
+            let gap = config.comment_gap;
+            write!(buf, "{:gap$}{}{}", "", config.comment_prefix, entry.comment)?;
+        }
+        writeln!(buf)?;
+        match entry.loan {
+            Some(loan) if config.loan_tag => writeln!(
+                buf,
+                "{}{}loan: {}",
+                config.indent, config.comment_prefix, loan
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn balance(&mut self, buf: &mut dyn Write, _entry: &Entry, config: &Config) -> io::Result<()> {
+        writeln!(buf, "{}{}", config.indent, config.asset)
+    }
+
+    fn pay(
+        &mut self,
+        buf: &mut dyn Write,
+        entry: &Entry,
+        p: &Posting,
+        config: &Config,
+    ) -> io::Result<()> {
+        posting(buf, config, &p.account, entry, p)
+    }
+}
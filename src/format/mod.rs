@@ -0,0 +1,209 @@
+//! Output formats for [`Entry`].
+use crate::{Config, Entry, Posting};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+mod beancount;
+mod csv;
+mod json;
+mod ledger;
+mod ofx;
+mod qif;
+
+pub use beancount::Beancount;
+pub use csv::Csv;
+pub use json::Json;
+pub use ledger::Ledger;
+pub use ofx::Ofx;
+pub use qif::Qif;
+
+/// Writes entries in some plain text accounting format.
+///
+/// [`write_entries`](crate::write_entries) calls `header`, `balance`, each
+/// `pay` and `end` for every entry, between a single `prelude` and `finish`.
+/// `opening` comes right after `prelude` when enabled and `closing` after the
+/// entries with [`Entry::closing`].
+pub trait Format {
+    /// Writes anything needed before the first entry.
+    fn prelude(
+        &mut self,
+        _buf: &mut dyn Write,
+        _entries: &[Entry],
+        _config: &Config,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes comment lines and an empty line, used for the statement periods
+    /// and pages.
+    fn comment(&mut self, _buf: &mut dyn Write, _text: &str, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes the transaction line.
+    fn header(&mut self, buf: &mut dyn Write, entry: &Entry, config: &Config) -> io::Result<()>;
+
+    /// Writes the statement account posting which takes the balance.
+    fn balance(&mut self, buf: &mut dyn Write, entry: &Entry, config: &Config) -> io::Result<()>;
+
+    /// Writes a payment line.
+    fn pay(
+        &mut self,
+        buf: &mut dyn Write,
+        entry: &Entry,
+        posting: &Posting,
+        config: &Config,
+    ) -> io::Result<()>;
+
+    /// Ends the entry, by default with an empty line.
+    fn end(&mut self, buf: &mut dyn Write, _entry: &Entry, _config: &Config) -> io::Result<()> {
+        writeln!(buf)
+    }
+
+    /// Writes the balance of the statement account before the first entry, for
+    /// `opening_balance`.
+    fn opening(
+        &mut self,
+        _buf: &mut dyn Write,
+        _date: NaiveDate,
+        _balance: Decimal,
+        _commodity: &str,
+        _config: &Config,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes an assertion of the statement account balance after the row of
+    /// the date.
+    fn closing(
+        &mut self,
+        _buf: &mut dyn Write,
+        _date: NaiveDate,
+        _balance: Decimal,
+        _commodity: &str,
+        _config: &Config,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes anything needed after the last entry.
+    fn finish(&mut self, _buf: &mut dyn Write, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Formats available with `--format`.
+#[derive(Clone, Copy)]
+pub enum OutputFormat {
+    Ledger,
+    Beancount,
+    Csv,
+    Json,
+    Qif,
+    Ofx,
+}
+
+impl OutputFormat {
+    pub fn formatter(self) -> Box<dyn Format> {
+        match self {
+            OutputFormat::Ledger => Box::new(Ledger),
+            OutputFormat::Beancount => Box::new(Beancount),
+            OutputFormat::Csv => Box::new(Csv),
+            OutputFormat::Json => Box::new(Json::default()),
+            OutputFormat::Qif => Box::new(Qif),
+            OutputFormat::Ofx => Box::new(Ofx::default()),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ledger" => Ok(OutputFormat::Ledger),
+            "beancount" => Ok(OutputFormat::Beancount),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            "qif" => Ok(OutputFormat::Qif),
+            "ofx" => Ok(OutputFormat::Ofx),
+            _ => Err(format!("unknown format {:?}", s)),
+        }
+    }
+}
+
+/// Writer which turns `\n` into `\r\n`, for `--line-ending crlf`.
+pub struct Crlf<W>(pub W);
+
+impl<W: Write> Write for Crlf<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (i, line) in buf.split(|&b| b == b'\n').enumerate() {
+            if i > 0 {
+                self.0.write_all(b"\r\n")?;
+            }
+            self.0.write_all(line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Commodities of the entries in the order they first appear, the one in the
+/// config if there are none.
+fn commodities<'a>(entries: &'a [Entry], config: &'a Config) -> Vec<&'a str> {
+    let mut commodities = Vec::new();
+    for entry in entries {
+        if !commodities.contains(&entry.commodity) {
+            commodities.push(entry.commodity);
+        }
+    }
+    if commodities.is_empty() {
+        commodities.push(config.commodity());
+    }
+    commodities
+}
+
+/// Spaces before an amount of `width` columns so it ends at `config.width`, at
+/// least one so ledger sees two spaces with the separator after it.
+fn pad(config: &Config, acc: &str, width: usize) -> usize {
+    config
+        .width
+        .saturating_sub(config.indent.width() + acc.len() + width + 1)
+        .max(1)
+}
+
+/// Writes an indented posting with the amount aligned to `config.width`, or
+/// two spaces after the account if it does not fit.
+fn posting(
+    buf: &mut dyn Write,
+    config: &Config,
+    acc: &str,
+    entry: &Entry,
+    p: &Posting,
+) -> io::Result<()> {
+    let amt = config.amount(p.amount);
+    write!(
+        buf,
+        "{}{}{:pad$} {} {}{:gap$}{}{}",
+        config.indent,
+        acc,
+        "",
+        amt,
+        entry.commodity,
+        "",
+        config.comment_prefix,
+        p.comment,
+        pad = pad(config, acc, amt.len()),
+        gap = config.comment_gap
+    )?;
+    if config.inline_balance {
+        let prefix = config.comment_prefix.trim_end();
+        write!(buf, "{} bal {}", prefix, config.assertion(p.txn.total))?;
+    }
+    writeln!(buf)
+}
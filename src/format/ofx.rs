@@ -0,0 +1,120 @@
+use super::Format;
+use crate::{Config, Entry, Posting};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// OFX 1.x SGML bank statement for the statement account, one transaction for
+/// each posting.
+///
+/// Amounts have the opposite sign of the postings like [`Qif`](super::Qif).
+/// The transaction id is the date with the loan id, or the payee if there is
+/// none, numbered when repeated so importers can skip rows seen before.
+#[derive(Default)]
+pub struct Ofx {
+    ids: HashMap<String, usize>,
+    /// Date and running total of the last transaction.
+    balance: Option<(NaiveDate, String)>,
+}
+
+/// Escapes the characters with a meaning in SGML.
+fn text(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace(['\r', '\n'], " ")
+}
+
+fn date(date: NaiveDate) -> impl std::fmt::Display {
+    date.format("%Y%m%d")
+}
+
+impl Format for Ofx {
+    fn prelude(
+        &mut self,
+        buf: &mut dyn Write,
+        entries: &[Entry],
+        config: &Config,
+    ) -> io::Result<()> {
+        let dates = entries.iter().map(|entry| entry.date);
+        let start = dates.clone().min().unwrap_or_default();
+        let end = dates.max().unwrap_or_default();
+        writeln!(
+            buf,
+            "OFXHEADER:100\nDATA:OFXSGML\nVERSION:102\nSECURITY:NONE\n\
+             ENCODING:USASCII\nCHARSET:1252\nCOMPRESSION:NONE\n\
+             OLDFILEUID:NONE\nNEWFILEUID:NONE\n"
+        )?;
+        writeln!(buf, "<OFX>\n<SIGNONMSGSRSV1>\n<SONRS>")?;
+        writeln!(buf, "<STATUS>\n<CODE>0\n<SEVERITY>INFO\n</STATUS>")?;
+        writeln!(buf, "<DTSERVER>{}\n<LANGUAGE>ENG", date(end))?;
+        writeln!(buf, "</SONRS>\n</SIGNONMSGSRSV1>")?;
+        writeln!(buf, "<BANKMSGSRSV1>\n<STMTTRNRS>\n<TRNUID>0")?;
+        writeln!(buf, "<STATUS>\n<CODE>0\n<SEVERITY>INFO\n</STATUS>")?;
+        // one currency for the whole statement, the first one of the entries
+        let commodity = entries
+            .first()
+            .map_or(config.commodity(), |entry| entry.commodity);
+        writeln!(buf, "<STMTRS>\n<CURDEF>{}", text(commodity))?;
+        writeln!(buf, "<BANKACCTFROM>\n<BANKID>0")?;
+        writeln!(buf, "<ACCTID>{}\n<ACCTTYPE>CHECKING", text(&config.asset))?;
+        writeln!(buf, "</BANKACCTFROM>\n<BANKTRANLIST>")?;
+        writeln!(buf, "<DTSTART>{}\n<DTEND>{}", date(start), date(end))
+    }
+
+    fn header(&mut self, _buf: &mut dyn Write, _entry: &Entry, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn balance(
+        &mut self,
+        _buf: &mut dyn Write,
+        _entry: &Entry,
+        _config: &Config,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pay(
+        &mut self,
+        buf: &mut dyn Write,
+        entry: &Entry,
+        p: &Posting,
+        _config: &Config,
+    ) -> io::Result<()> {
+        let amount = -p.amount;
+        let kind = if amount.is_sign_negative() {
+            "DEBIT"
+        } else {
+            "CREDIT"
+        };
+        let id = format!("{}-{}", date(entry.date), entry.loan.unwrap_or(entry.payee));
+        let n = self.ids.entry(id.clone()).or_insert(0);
+        *n += 1;
+        // NAME is limited to 32 characters
+        let name: String = entry.payee.chars().take(32).collect();
+        writeln!(buf, "<STMTTRN>\n<TRNTYPE>{}", kind)?;
+        writeln!(buf, "<DTPOSTED>{}\n<TRNAMT>{:.2}", date(entry.date), amount)?;
+        writeln!(buf, "<FITID>{}-{}", text(&id), n)?;
+        writeln!(buf, "<NAME>{}\n<MEMO>{}", text(&name), text(p.comment))?;
+        writeln!(buf, "</STMTTRN>")?;
+        self.balance = Some((entry.date, format!("{:.2}", p.txn.total)));
+        Ok(())
+    }
+
+    fn end(&mut self, _buf: &mut dyn Write, _entry: &Entry, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self, buf: &mut dyn Write, _config: &Config) -> io::Result<()> {
+        writeln!(buf, "</BANKTRANLIST>")?;
+        // required even without transactions
+        let (day, total) = self
+            .balance
+            .take()
+            .unwrap_or_else(|| (NaiveDate::default(), "0.00".to_owned()));
+        writeln!(buf, "<LEDGERBAL>\n<BALAMT>{}", total)?;
+        writeln!(buf, "<DTASOF>{}\n</LEDGERBAL>", date(day))?;
+        writeln!(buf, "</STMTRS>\n</STMTTRNRS>\n</BANKMSGSRSV1>\n</OFX>")
+    }
+}
@@ -0,0 +1,54 @@
+use super::Format;
+use crate::{Config, Entry, Posting};
+use std::io::{self, Write};
+
+/// Quicken interchange format with one bank record per posting.
+///
+/// The records are for the statement account, so amounts have the opposite
+/// sign of the postings and the posting account becomes the category.
+pub struct Qif;
+
+impl Format for Qif {
+    fn prelude(
+        &mut self,
+        buf: &mut dyn Write,
+        _entries: &[Entry],
+        _config: &Config,
+    ) -> io::Result<()> {
+        writeln!(buf, "!Type:Bank")
+    }
+
+    fn header(&mut self, _buf: &mut dyn Write, _entry: &Entry, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn balance(
+        &mut self,
+        _buf: &mut dyn Write,
+        _entry: &Entry,
+        _config: &Config,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pay(
+        &mut self,
+        buf: &mut dyn Write,
+        entry: &Entry,
+        p: &Posting,
+        _config: &Config,
+    ) -> io::Result<()> {
+        // one field per line, so newlines cannot be kept
+        let line = |s: &str| s.replace(['\r', '\n'], " ");
+        writeln!(buf, "D{}", entry.date.format("%m/%d/%Y"))?;
+        writeln!(buf, "T{:.2}", -p.amount)?;
+        writeln!(buf, "P{}", line(entry.payee))?;
+        writeln!(buf, "M{}", line(p.comment))?;
+        writeln!(buf, "L{}", line(&p.account))?;
+        writeln!(buf, "^")
+    }
+
+    fn end(&mut self, _buf: &mut dyn Write, _entry: &Entry, _config: &Config) -> io::Result<()> {
+        Ok(())
+    }
+}
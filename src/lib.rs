@@ -0,0 +1,897 @@
+//! Parse funding societies account statement into plain text ledger.
+//!
+//! ```no_run
+//! # fn main() -> fs_ledger::Result<()> {
+//! let txns = fs_ledger::transactions_from_pdf("statement.pdf")?;
+//! let config = fs_ledger::Config::default();
+//! fs_ledger::write_ledger(&mut std::io::stdout(), &txns, &config)?;
+//! # Ok(())
+//! # }
+//! ```
+use chrono::{Datelike, NaiveDate};
+use log::{debug, warn};
+use regex::Regex;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[macro_use]
+mod date;
+pub mod amount;
+mod config;
+mod error;
+pub mod format;
+mod reader;
+
+pub use config::{Config, Flag, Indent, Rule};
+pub use date::DateOrder;
+pub use error::{LedgerError, Result};
+pub use format::{Beancount, Crlf, Csv, Format, Json, Ledger, Ofx, OutputFormat, Qif};
+pub use reader::read_ledger;
+
+/// A row in the statement table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Transaction {
+    pub date: NaiveDate,
+    /// Description, without the comment after `||`.
+    pub title: String,
+    /// Comment after `||`, empty if none.
+    pub comment: String,
+    /// Amount in the debit column, in parentheses in the statement.
+    pub debit: Decimal,
+    pub credit: Decimal,
+    /// Running balance after this row.
+    pub total: Decimal,
+    /// Commodity declared in the header of the statement, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commodity: Option<&'static str>,
+    /// Page of the statement the row starts on, counting from 1, only kept
+    /// with `page_comments`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+}
+
+/// Transactions of a statement.
+pub struct Statement {
+    /// Commodity declared in the table header, if known.
+    pub commodity: Option<&'static str>,
+    /// First and last day of the statement period in the header, if found.
+    pub period: Option<(NaiveDate, NaiveDate)>,
+    pub transactions: Vec<Transaction>,
+    /// Errors from pdftotext, only kept with `allow_errors`.
+    pub errors: Vec<String>,
+}
+
+/// Currency symbols in the statement header and their commodity.
+const SYMBOLS: &[(&str, &str)] = &[("RM", "MYR"), ("S$", "SGD"), ("Rp", "IDR"), ("$", "USD")];
+
+/// Maps the currency symbol or code in the statement header to a commodity.
+fn commodity_from_symbol(symbol: &str) -> Option<&'static str> {
+    let symbol = symbol.trim();
+    let commodity = SYMBOLS
+        .iter()
+        .find(|&&(sym, commodity)| sym == symbol || commodity == symbol)
+        .map(|&(_, commodity)| commodity);
+    if commodity.is_none() {
+        warn!(
+            "unknown currency {:?} in the header, using the commodity in the config",
+            symbol
+        );
+    }
+    commodity
+}
+
+/// Kinds of poppler messages which mean the text may be incomplete, others
+/// like `Syntax Warning` are only logged.
+const POPPLER_ERRORS: &[&str] = &[
+    "Syntax Error",
+    "Config Error",
+    "Command Line Error",
+    "I/O Error",
+    "Permission Error",
+    "Internal Error",
+];
+
+/// Converts pdf into text with pdftotext, failing if it reports any error.
+pub fn pdf_to_text(path: impl AsRef<Path>) -> Result<String> {
+    let (text, errors) = pdftotext(path.as_ref().as_os_str(), None, false)?;
+    if !errors.is_empty() {
+        return Err(LedgerError::Extraction(errors));
+    }
+    Ok(text)
+}
+
+/// Runs pdftotext, returns the text and errors reported while extracting it,
+/// warnings are logged.
+///
+/// The pdf is written to its stdin if given, with `input` as `-`. Pages are
+/// separated by form feeds with `pages`.
+fn pdftotext(input: &OsStr, pdf: Option<&[u8]>, pages: bool) -> Result<(String, Vec<String>)> {
+    let failed = |e| LedgerError::PdfLoad(format!("cannot run pdftotext: {}", e));
+    // TODO: use something else since hyphenation is broken in some cases
+    let mut cmd = Command::new("pdftotext");
+    if !pages {
+        cmd.arg("-nopgbrk");
+    }
+    let mut child = cmd
+        .arg(input)
+        .arg("-")
+        .stdin(if pdf.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(failed)?;
+    let stdin = child.stdin.take();
+    // written from another thread so a full stdout pipe cannot block it
+    let output = thread::scope(|s| {
+        if let (Some(mut stdin), Some(pdf)) = (stdin, pdf) {
+            s.spawn(move || stdin.write_all(pdf));
+        }
+        child.wait_with_output()
+    })
+    .map_err(failed)?;
+    if !output.status.success() {
+        return Err(LedgerError::pdftotext(output.status, &output.stderr));
+    }
+    let mut errors = Vec::new();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stderr.lines().filter(|line| !line.trim().is_empty()) {
+        if POPPLER_ERRORS.iter().any(|kind| line.starts_with(kind)) {
+            errors.push(line.to_owned());
+        } else {
+            warn!("{}: {}", Path::new(input).display(), line);
+        }
+    }
+    let text = String::from_utf8(output.stdout)
+        .map_err(|e| LedgerError::PdfLoad(format!("pdftotext output is not utf-8: {}", e)))?;
+    Ok((text, errors))
+}
+
+/// Pushes words to `buf` separated by a single space.
+fn join_words<'a>(buf: &mut String, words: impl Iterator<Item = &'a str>) {
+    for (i, word) in words.enumerate() {
+        if i > 0 {
+            buf.push(' ');
+        }
+        buf.push_str(word);
+    }
+}
+
+/// Joins words with a single space, text wrapped across lines has more.
+fn squeeze(s: &str) -> String {
+    let mut buf = String::with_capacity(s.len());
+    join_words(&mut buf, s.split_whitespace());
+    buf
+}
+
+/// Replaces non-breaking spaces and collapses runs of spaces in each line.
+fn normalize(src: &str) -> String {
+    let mut buf = String::with_capacity(src.len());
+    for (i, line) in src.split('\n').enumerate() {
+        if i > 0 {
+            buf.push('\n');
+        }
+        let words = line.split([' ', '\t', '\u{a0}']);
+        join_words(&mut buf, words.filter(|word| !word.is_empty()));
+    }
+    buf
+}
+
+/// Removes the form feeds pdftotext writes between pages, returns the text
+/// with the offsets where the pages after the first start.
+fn page_breaks(src: &str) -> (String, Vec<usize>) {
+    let mut text = String::with_capacity(src.len());
+    let mut breaks = Vec::new();
+    for (i, page) in src.split('\x0c').enumerate() {
+        if i > 0 {
+            breaks.push(text.len());
+        }
+        text.push_str(page);
+    }
+    (text, breaks)
+}
+
+/// Moves the end of a description broken by a page break back before its
+/// amounts, seen in 2023.
+///
+/// From `Early Payment\n\n(0.00)\n\n0.05\n\n140.45\n\nFee\n2023-01-10`
+///   to `Early Payment Fee\n\n(0.00)\n\n0.05\n\n140.45\n\n2023-01-10`
+fn unwrap_descriptions(src: &str) -> String {
+    let amount = Regex::new(r"\A\(?-?[0-9][[0-9],.]*\)?\z").unwrap();
+    let date = Regex::new(concat!(r"\A", date_pattern!(), r"\z")).unwrap();
+    let is_amount = |line: &str| line == "()" || amount.is_match(line);
+    let mut lines: Vec<Cow<str>> = src.split('\n').map(Cow::Borrowed).collect();
+    let mut i = 2;
+    while i + 6 < lines.len() {
+        // debit in parentheses, credit and balance, each after an empty line
+        let row = lines[i].starts_with('(')
+            && (i - 1..i + 5)
+                .step_by(2)
+                .all(|j| lines[j].is_empty() && is_amount(&lines[j + 1]))
+            && lines[i + 5].is_empty();
+        if !row {
+            i += 1;
+            continue;
+        }
+        // the rest of the description is right before the next date
+        let start = i + 6;
+        let end = (start..lines.len()).find(|&j| lines[j].is_empty() || date.is_match(&lines[j]));
+        if let Some(end) = end.filter(|&end| end > start && date.is_match(&lines[end])) {
+            let rest = lines.drain(start..end).collect::<Vec<_>>().join(" ");
+            let desc = lines[i - 2].to_mut();
+            desc.push(' ');
+            desc.push_str(&rest);
+        }
+        i += 6;
+    }
+    lines.join("\n")
+}
+
+/// Rows of a statement parsed one at a time as they are iterated.
+///
+/// The whole text of the statement is kept, only the rows are parsed lazily.
+///
+/// Parsing stops at the first error, which is the last item.
+pub struct TransactionIter {
+    src: String,
+    pos: usize,
+    re: Regex,
+    commodity: Option<&'static str>,
+    period: Option<(NaiveDate, NaiveDate)>,
+    has_end: bool,
+    order: DateOrder,
+    rows: usize,
+    done: bool,
+    /// Text before the rows are joined and where its pages start, to find
+    /// the page of each row with `page_comments`.
+    pages: Option<(String, Vec<usize>)>,
+    cursor: usize,
+}
+
+impl TransactionIter {
+    /// Finds the table in the text of a statement from [`pdf_to_text`].
+    pub fn new(src: &str, config: &Config) -> Result<Self> {
+        let (text, breaks) = page_breaks(&normalize(src));
+        let src = &text[..];
+        // pre-2022 uses `| |`, after that it uses `||`
+        // zero may also be `-0.00`, `0` or an empty debit `()`
+        // amounts may use either `.` or `,` before the cents
+        let re = Regex::new(concat!(r"\A (", date_pattern!(), r")  (.*?)(?: \| ?\| (.+?))?  \((-?(?:[0-9][[0-9],.]*)?)\)  (-?[0-9][[0-9],.]*)  (-?[0-9][[0-9],.]*) ")).unwrap();
+
+        // Statement Period: 01 Jan 2024 - 31 Jan 2024
+        let period =
+            Regex::new(r"Statement Period:?[ \n]+([^\n]+?)[ \n]+(?:-|to)[ \n]+([^\n]+?)\n")
+                .unwrap()
+                .captures(src)
+                .and_then(|cap| {
+                    // a period which is not a date is ignored, it is only shown
+                    let day = |s: &str| date::parse(s, config.date_order).ok();
+                    Some((day(&cap[1])?, day(&cap[2])?))
+                });
+
+        // take only table
+        // pre-2022 uses `Balance (RM)\n`, after that it uses `Balance\n(RM)\n`
+        let table = Regex::new(r"Balance[\n ]\(([^)\n]+)\)\n").unwrap();
+        let (commodity, start) = match table.captures(src) {
+            Some(table) => (
+                commodity_from_symbol(&table[1]),
+                table.get(0).unwrap().end(),
+            ),
+            // older layout without the header, start from the first date on its own line
+            None => {
+                let row = Regex::new(concat!("(?m)^", date_pattern!(), "$"))
+                    .unwrap()
+                    .find(src)
+                    .ok_or(LedgerError::MissingMarker("table start `Balance (RM)`"))?;
+                (None, row.start().saturating_sub(1))
+            }
+        };
+        let src = &src[start..];
+        // some variants end with a different footer, which is skipped after the last row
+        let (src, has_end) = match src.rsplit_once("\nImportant!\n") {
+            Some((table, _)) => (table, true),
+            None => (src, false),
+        };
+
+        let src = unwrap_descriptions(src);
+
+        // convert to single line, sometimes newline appear in middle
+        Ok(TransactionIter {
+            src: src.replace('\n', " "),
+            pos: 0,
+            re,
+            commodity,
+            period,
+            has_end,
+            order: config.date_order,
+            rows: 0,
+            done: false,
+            pages: config.page_comments.then_some((text, breaks)),
+            cursor: start,
+        })
+    }
+
+    /// Commodity declared in the table header, if known.
+    pub fn commodity(&self) -> Option<&'static str> {
+        self.commodity
+    }
+
+    /// Statement period in the header, if found.
+    pub fn period(&self) -> Option<(NaiveDate, NaiveDate)> {
+        self.period
+    }
+
+    fn row(&self, cap: &regex::Captures) -> Result<Transaction> {
+        Ok(Transaction {
+            page: None,
+            commodity: self.commodity,
+            date: date::parse(&cap[1], self.order)?,
+            title: squeeze(&cap[2]),
+            comment: squeeze(cap.get(3).map_or("", |m| m.as_str())),
+            debit: match &cap[4] {
+                "" => Decimal::ZERO,
+                debit => amount::parse(debit)?,
+            },
+            credit: amount::parse(&cap[5])?,
+            total: amount::parse(&cap[6])?,
+        })
+    }
+}
+
+impl Iterator for TransactionIter {
+    type Item = Result<Transaction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let src = &self.src[self.pos..];
+        let Some(cap) = self.re.captures(src) else {
+            self.done = true;
+            let row_start = Regex::new(concat!(" ", date_pattern!(), "  ")).unwrap();
+            if !src.is_empty() && (self.has_end || row_start.is_match(src)) {
+                let near = src.chars().take(60).collect();
+                let row = self.rows + 1;
+                return Some(Err(LedgerError::MalformedRow { row, near }));
+            }
+            return None;
+        };
+        self.pos += cap[0].len();
+        self.rows += 1;
+        debug!("row {}: {}", self.rows, cap[0].trim());
+        let mut txn = self.row(&cap);
+        self.done = txn.is_err();
+        // the row starts with its date, rows are found in order
+        if let (Ok(txn), Some((text, breaks))) = (&mut txn, &self.pages) {
+            if let Some(start) = text[self.cursor..].find(&cap[1]) {
+                let start = self.cursor + start;
+                self.cursor = start + cap[1].len();
+                txn.page = Some(breaks.partition_point(|&b| b <= start) + 1);
+            }
+        }
+        Some(txn)
+    }
+}
+
+/// Parses the text of a statement from [`pdf_to_text`].
+pub fn parse_statement(src: &str, config: &Config) -> Result<Statement> {
+    let iter = TransactionIter::new(src, config)?;
+    Ok(Statement {
+        commodity: iter.commodity(),
+        period: iter.period(),
+        transactions: iter.collect::<Result<_>>()?,
+        errors: Vec::new(),
+    })
+}
+
+/// Reads a statement from pdf, errors are prefixed with the path.
+///
+/// A `.txt` file is taken as text already extracted by pdftotext.
+pub fn read_statement(path: impl AsRef<Path>, config: &Config) -> Result<Statement> {
+    let path = path.as_ref();
+    let is_text = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"));
+    let read = || {
+        let (src, errors) = if path == Path::new("-") {
+            read_stdin(config)?
+        } else if !path.exists() {
+            // pdftotext only says it could not open the file
+            return Err(LedgerError::NotFound);
+        } else if is_text {
+            (fs::read_to_string(path)?, Vec::new())
+        } else {
+            pdftotext(path.as_os_str(), None, config.page_comments)?
+        };
+        if !errors.is_empty() && !config.allow_errors {
+            return Err(LedgerError::Extraction(errors));
+        }
+        let statement = parse_statement(&src, config)?;
+        Ok(Statement {
+            errors,
+            ..statement
+        })
+    };
+    read().map_err(|e| LedgerError::Statement {
+        path: path.to_owned(),
+        source: Box::new(e),
+    })
+}
+
+/// Reads a statement given as `-` from stdin, text unless it starts like a pdf.
+fn read_stdin(config: &Config) -> Result<(String, Vec<String>)> {
+    let mut buf = Vec::new();
+    io::stdin().read_to_end(&mut buf)?;
+    if buf.starts_with(b"%PDF") {
+        return pdftotext("-".as_ref(), Some(&buf), config.page_comments);
+    }
+    let text = String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((text, Vec::new()))
+}
+
+/// Reads statements with up to `jobs` threads, results are in the order of paths.
+pub fn read_statements<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    config: &Config,
+    jobs: usize,
+) -> Vec<Result<Statement>> {
+    let next = AtomicUsize::new(0);
+    let worker = || {
+        let mut done = Vec::new();
+        loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            match paths.get(i) {
+                Some(path) => done.push((i, read_statement(path, config))),
+                None => return done,
+            }
+        }
+    };
+    let mut results: Vec<_> = paths.iter().map(|_| None).collect();
+    thread::scope(|s| {
+        let workers: Vec<_> = (0..jobs.clamp(1, paths.len().max(1)))
+            .map(|_| s.spawn(worker))
+            .collect();
+        for worker in workers {
+            for (i, result) in worker.join().unwrap() {
+                results[i] = Some(result);
+            }
+        }
+    });
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+/// Reads transactions from a statement pdf with the default config.
+pub fn transactions_from_pdf(path: impl AsRef<Path>) -> Result<Vec<Transaction>> {
+    Ok(read_statement(path, &Config::default())?.transactions)
+}
+
+/// Latest date of the transactions in a ledger file, from lines starting with a
+/// date like `2020-01-31 * payee`.
+pub fn last_date(ledger: &str, order: DateOrder) -> Option<NaiveDate> {
+    ledger
+        .lines()
+        .filter(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+        .filter_map(|line| {
+            // ledger allows an auxiliary date like `2020-01-31=2020-02-01`
+            let date = line.split_whitespace().next()?.split('=').next()?;
+            date::parse(date, order).ok()
+        })
+        .max()
+}
+
+/// A row whose running balance differs from the previous row with its amounts.
+pub struct Mismatch<'a> {
+    pub txn: &'a Transaction,
+    /// Balance computed from the previous row.
+    pub expected: Decimal,
+}
+
+/// Checks the running balance of each row against the previous row.
+pub fn verify(txns: &[Transaction]) -> Vec<Mismatch<'_>> {
+    txns.windows(2)
+        .filter_map(|pair| {
+            let expected = pair[0].total + pair[1].credit - pair[1].debit;
+            (expected != pair[1].total).then_some(Mismatch {
+                txn: &pair[1],
+                expected,
+            })
+        })
+        .collect()
+}
+
+/// Drops repeated rows, like those at the boundary of overlapping statements.
+///
+/// Rows are the same if their running balance also matches, so equal rows of
+/// the same statement like two fees on a day are kept. The first one is kept.
+pub fn dedup(txns: &mut Vec<Transaction>) {
+    let mut seen = HashSet::new();
+    txns.retain(|txn| {
+        let key = (
+            txn.date,
+            txn.title.clone(),
+            txn.comment.clone(),
+            txn.debit,
+            txn.credit,
+            txn.total,
+        );
+        seen.insert(key)
+    });
+}
+
+/// Problem between two statements found by [`check_periods`], by their index.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Period {
+    /// The second statement starts on or before the last row of the first.
+    Overlap(usize, usize),
+    /// At least a month without rows between the statements.
+    Gap(usize, usize),
+}
+
+/// Checks the dates of the first and last row of each statement, in the
+/// order they start, for overlaps and missing months.
+pub fn check_periods(ranges: &[(NaiveDate, NaiveDate)]) -> Vec<Period> {
+    let month = |date: NaiveDate| date.year() * 12 + date.month0() as i32;
+    let mut order: Vec<_> = (0..ranges.len()).collect();
+    order.sort_by_key(|&i| ranges[i].0);
+    order
+        .windows(2)
+        .filter_map(|pair| {
+            let (prev, next) = (ranges[pair[0]], ranges[pair[1]]);
+            if next.0 <= prev.1 {
+                Some(Period::Overlap(pair[0], pair[1]))
+            } else if month(next.0) > month(prev.1) + 1 {
+                Some(Period::Gap(pair[0], pair[1]))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// A transaction in the output, built from one or more rows.
+pub struct Entry<'a> {
+    pub date: NaiveDate,
+    pub payee: &'a str,
+    /// Comment on the transaction, empty if none.
+    pub comment: &'a str,
+    /// Loan id like `XXXX-00000000` of investments, repayments and adjustments.
+    pub loan: Option<&'a str>,
+    /// Commodity of the statement of the rows, or the one in the config.
+    pub commodity: &'a str,
+    /// Postings besides the statement account, which takes the balance.
+    pub postings: Vec<Posting<'a>>,
+    /// Rows the entry is built from, also those with nothing to post.
+    pub rows: Vec<Row<'a>>,
+    /// Row whose balance is asserted after the entry, see [`mark_closing`].
+    pub closing: Option<&'a Transaction>,
+    /// Statement period written before the entry, see [`mark_periods`].
+    pub period: Option<(NaiveDate, NaiveDate)>,
+}
+
+/// A posting in an [`Entry`].
+pub struct Posting<'a> {
+    /// Account, with the loan id appended to `funds` if `per_loan`.
+    pub account: Cow<'a, str>,
+    /// Debit is positive and credit is negative.
+    pub amount: Decimal,
+    pub comment: &'a str,
+    /// Row this posting came from.
+    pub txn: &'a Transaction,
+}
+
+/// A row in an [`Entry`] with the account it is classified to.
+pub struct Row<'a> {
+    pub txn: &'a Transaction,
+    /// Account of the first posting of the row, split rows like service fees
+    /// with `sst_rate` also post to other accounts.
+    pub account: Cow<'a, str>,
+}
+
+/// Posts a row which cannot be classified to `config.unknown`, unless strict.
+fn unknown<'a>(txn: &Transaction, config: &'a Config) -> Result<(&'a str, Decimal)> {
+    if config.strict {
+        return Err(LedgerError::UnknownTransaction(Box::new(txn.clone())));
+    }
+    debug!(
+        "{} {:?} with comment {:?} is unknown",
+        txn.date, txn.title, txn.comment
+    );
+    Ok((&config.unknown, txn.debit - txn.credit))
+}
+
+/// Strips the prefix of a reverted row, its amount already has the opposite
+/// sign so it is posted to the same account as the original.
+fn reverted(s: &str) -> &str {
+    ["Revert ", "Reversal of ", "Reversal "]
+        .iter()
+        .find_map(|prefix| s.strip_prefix(prefix))
+        .unwrap_or(s)
+}
+
+/// Maps repayment rows to accounts, used by [`entries_with`].
+///
+/// Deposits, withdrawals and investments always go to the accounts in
+/// [`Config`], repayment rows like `Interest` are passed here.
+pub trait Classifier {
+    /// Account of a row, `None` if unknown.
+    fn account(&self, txn: &Transaction) -> Option<&str>;
+}
+
+/// Classifies by the rules, `comments` and the built-in comments, reverted
+/// rows like their original.
+impl Classifier for Config {
+    fn account(&self, txn: &Transaction) -> Option<&str> {
+        Config::account(self, &txn.title, reverted(&txn.comment))
+    }
+}
+
+/// Classifies rows into entries, rows of the same repayment are merged.
+pub fn entries<'a>(txns: &'a [Transaction], config: &'a Config) -> Result<Vec<Entry<'a>>> {
+    entries_with(txns, config, config)
+}
+
+/// Classifies rows into entries like [`entries`] with repayment rows mapped
+/// by `classifier`.
+pub fn entries_with<'a>(
+    txns: &'a [Transaction],
+    config: &'a Config,
+    classifier: &'a dyn Classifier,
+) -> Result<Vec<Entry<'a>>> {
+    let mut entries = Vec::new();
+    let mut txns = txns.iter().peekable();
+    while let Some(mut txn) = txns.next() {
+        let mut comment = "";
+        let payee = if txn.title.starts_with("Deposit") || txn.title.starts_with("Withdrawal") {
+            comment = &txn.comment;
+            "Funding Societies"
+        } else if txn.title.contains("invested") {
+            // Auto Investment: invested 100 into XXXX-00000000
+            txn.title.rsplit("into ").next().unwrap()
+        } else if txn.title.ends_with("repayment)") {
+            // XXXX-00000000 (1 of 1 repayment), sometimes without the space
+            match txn.title.rsplit_once('(') {
+                Some((loan, part)) => {
+                    comment = part.trim_end_matches(')');
+                    reverted(loan.trim_end())
+                }
+                None => reverted(&txn.title),
+            }
+        } else {
+            reverted(&txn.title)
+        };
+        let loan = if txn.title.contains("invested") || txn.title.ends_with("repayment)") {
+            Some(payee)
+        } else {
+            txn.title.strip_prefix("Adjustment for investment to ")
+        };
+        let mut postings = Vec::new();
+        let mut rows = Vec::new();
+        let mut post = |txn: &'a Transaction, account: &'a str, amount: Decimal, comment| {
+            let account = match loan {
+                Some(loan) if config.per_loan && account == config.funds => {
+                    Cow::Owned(format!("{}:{}", account, loan))
+                }
+                _ => Cow::Borrowed(account),
+            };
+            if rows.last().is_none_or(|row: &Row| !ptr::eq(row.txn, txn)) {
+                rows.push(Row {
+                    txn,
+                    account: account.clone(),
+                });
+            }
+            // ledger complains about zero postings, like some adjustment rows
+            if amount.is_zero() {
+                return;
+            }
+            debug!(
+                "{} {:?} {:?} posted to {}",
+                txn.date, txn.title, comment, account
+            );
+            postings.push(Posting {
+                account,
+                amount,
+                comment,
+                txn,
+            })
+        };
+        if txn.title.contains("invested") {
+            let cmt = reverted(txn.title.split(": ").next().unwrap());
+            post(txn, &config.funds, txn.debit - txn.credit, cmt);
+        } else if txn.title.starts_with("Deposit") || txn.title.starts_with("Withdrawal") {
+            // a correction may fill both columns, each side gets its own posting
+            let bank = config.bank(&txn.title);
+            if txn.title.starts_with("Deposit") {
+                post(txn, bank, -txn.credit, &txn.title);
+                post(txn, bank, txn.debit, &txn.title);
+            } else {
+                post(txn, bank, txn.debit, &txn.title);
+                post(txn, bank, -txn.credit, &txn.title);
+            }
+        } else if txn.title.starts_with("Adjustment for investment to ") {
+            // only negative adjustment supported
+            if txn.debit.is_zero() {
+                post(txn, &config.funds, -txn.credit, "Adjustment");
+            } else {
+                let (acc, amt) = unknown(txn, config)?;
+                post(txn, acc, amt, "Adjustment");
+            }
+        } else {
+            // parse multiple lines of payment for the same transaction
+            loop {
+                let cmt = &txn.comment[..];
+                // debit is a revert of income or a fee, credit is the opposite
+                let (acc, amt) = match classifier.account(txn) {
+                    Some(acc) => (acc, txn.debit - txn.credit),
+                    None => unknown(txn, config)?,
+                };
+                match config.sst_rate {
+                    Some(rate) if reverted(cmt) == "Service Fee" => {
+                        // the charged fee includes the tax
+                        let hundred = Decimal::ONE_HUNDRED;
+                        let fee = (amt * hundred / (hundred + rate)).round_dp(2);
+                        post(txn, acc, fee, cmt);
+                        post(txn, &config.tax, amt - fee, "SST");
+                    }
+                    _ => post(txn, acc, amt, cmt),
+                }
+                let same = |next: &&Transaction| {
+                    next.date == txn.date
+                        && next.title == txn.title
+                        && next.commodity == txn.commodity
+                };
+                match txns.next_if(same) {
+                    Some(next) => txn = next,
+                    None => break,
+                }
+            }
+        }
+        entries.push(Entry {
+            date: txn.date,
+            payee,
+            comment,
+            loan,
+            commodity: txn.commodity.unwrap_or(config.commodity()),
+            postings,
+            rows,
+            closing: None,
+            period: None,
+        });
+    }
+    Ok(entries)
+}
+
+/// Counts postings to `config.unknown` by their comment.
+pub fn unknown_comments<'a>(entries: &[Entry<'a>], config: &Config) -> BTreeMap<&'a str, usize> {
+    let mut counts = BTreeMap::new();
+    for p in entries.iter().flat_map(|entry| &entry.postings) {
+        if p.account == config.unknown {
+            *counts.entry(p.comment).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Sums the amounts of postings by account.
+pub fn totals<'b>(entries: &'b [Entry]) -> BTreeMap<&'b str, Decimal> {
+    let mut totals = BTreeMap::new();
+    for p in entries.iter().flat_map(|entry| &entry.postings) {
+        *totals.entry(&*p.account).or_default() += p.amount;
+    }
+    totals
+}
+
+/// Asserts the balance after each of the rows, like the last row of each
+/// statement, on the entry built from the row or else the last one dated
+/// before it, so rows with nothing to post or filtered out are asserted too.
+pub fn mark_closing<'a>(entries: &mut [Entry<'a>], rows: &'a [Transaction]) {
+    for txn in rows {
+        let index = entries
+            .iter()
+            .position(|entry| entry.rows.iter().any(|row| row.txn == txn))
+            .or_else(|| entries.iter().rposition(|entry| entry.date <= txn.date));
+        match index {
+            Some(i) => entries[i].closing = Some(txn),
+            None => debug!("no entry before {} to assert the balance", txn.date),
+        }
+    }
+}
+
+/// Writes the period of each statement before the entry built from its first
+/// row, or else the first entry dated after it.
+pub fn mark_periods(entries: &mut [Entry], starts: &[(Transaction, (NaiveDate, NaiveDate))]) {
+    for (txn, period) in starts {
+        let unmarked = |entry: &&mut Entry| entry.period.is_none();
+        let entry = match entries
+            .iter_mut()
+            .filter(unmarked)
+            .find(|entry| entry.rows.iter().any(|row| row.txn == txn))
+        {
+            Some(entry) => Some(entry),
+            None => entries
+                .iter_mut()
+                .filter(unmarked)
+                .find(|entry| entry.date >= txn.date),
+        };
+        match entry {
+            Some(entry) => entry.period = Some(*period),
+            None => debug!("no entry after {} for the statement period", txn.date),
+        }
+    }
+}
+
+/// Smallest width that aligns the amounts of all postings in entries.
+pub fn auto_width(entries: &[Entry], config: &Config) -> usize {
+    entries
+        .iter()
+        .flat_map(|entry| &entry.postings)
+        .map(|p| config.indent.width() + p.account.len() + config.amount(p.amount).len() + 2)
+        .max()
+        .unwrap_or(config.width)
+}
+
+/// Writes entries with the given format.
+pub fn write_entries(
+    buf: &mut dyn Write,
+    entries: &[Entry],
+    format: &mut dyn Format,
+    config: &Config,
+) -> io::Result<()> {
+    format.prelude(buf, entries, config)?;
+    let first = entries
+        .first()
+        .and_then(|entry| Some((entry, entry.rows.first()?)));
+    if let (true, Some((entry, row))) = (config.opening_balance, first) {
+        let txn = row.txn;
+        let balance = txn.total - txn.credit + txn.debit;
+        format.opening(buf, txn.date, balance, entry.commodity, config)?;
+    }
+    let mut page = None;
+    for entry in entries {
+        if let Some((start, end)) = entry.period {
+            format.comment(buf, &format!("Statement {} to {}", start, end), config)?;
+        }
+        let entry_page = entry.postings.first().and_then(|p| p.txn.page);
+        if let (true, Some(n)) = (config.page_comments, entry_page) {
+            if page != entry_page {
+                format.comment(buf, &format!("--- page {} ---", n), config)?;
+                page = entry_page;
+            }
+        }
+        format.header(buf, entry, config)?;
+        format.balance(buf, entry, config)?;
+        for posting in &entry.postings {
+            format.pay(buf, entry, posting, config)?;
+        }
+        format.end(buf, entry, config)?;
+        if let Some(txn) = entry.closing {
+            let commodity = txn.commodity.unwrap_or(config.commodity());
+            format.closing(buf, txn.date, txn.total, commodity, config)?;
+        }
+        #[cfg(debug_assertions)]
+        buf.flush()?;
+    }
+    format.finish(buf, config)
+}
+
+/// Writes transactions as ledger.
+pub fn write_ledger(buf: &mut dyn Write, txns: &[Transaction], config: &Config) -> Result<()> {
+    Ok(write_entries(
+        buf,
+        &entries(txns, config)?,
+        &mut Ledger,
+        config,
+    )?)
+}
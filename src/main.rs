@@ -1,156 +1,637 @@
-use regex::Regex;
+use chrono::NaiveDate;
+use clap::{Parser, ValueEnum};
+use env_logger::Env;
+use fs_ledger::amount::{self, Negative};
+use fs_ledger::{
+    Config, Crlf, DateOrder, Entry, Flag, Indent, Ledger, LedgerError, OutputFormat, Period,
+    Transaction,
+};
+use log::{error, log, warn, Level};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
 use std::env;
-use std::fs::File;
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufWriter, Write};
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::thread;
 
-// accounts
-const ASSET: &str = "assets:fundingsocieties";
-const FUNDS: &str = "assets:funds:fundingsocieties";
-const BANK: &str = "assets:bank:pbe";
-const INCOME: &str = "income:interest";
-const EXPENSE: &str = "expenses:service";
+/// Expands glob patterns in inputs, matches are sorted by file name.
+fn expand_inputs(inputs: Vec<String>) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if !input.contains(['*', '?', '[']) {
+            paths.push(PathBuf::from(input));
+            continue;
+        }
+        // quoted patterns do not get `~` expanded by the shell
+        let pattern = match (input.strip_prefix("~/"), env::var("HOME")) {
+            (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+            _ => input.clone(),
+        };
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidInput, e);
+        let mut matches = glob::glob(&pattern)
+            .map_err(|e| invalid(format!("invalid pattern {:?}: {}", input, e)))?
+            .collect::<Result<Vec<_>, glob::GlobError>>()
+            .map_err(io::Error::from)?;
+        if matches.is_empty() {
+            let msg = format!("no statement matches {:?}", input);
+            return Err(io::Error::new(io::ErrorKind::NotFound, msg));
+        }
+        matches.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+        paths.extend(matches);
+    }
+    Ok(paths)
+}
 
-const COMMODITY: &str = "MYR";
-const INDENT: &str = "\t";
-const LINE_WIDTH: usize = 62;
+/// Logs up to `max` errors, all of them if `max` is 0.
+fn report_errors(input: &Path, errors: &[String], max: usize, level: Level) {
+    let shown = if max == 0 { errors.len() } else { max };
+    for error in errors.iter().take(shown) {
+        log!(level, "{}: {}", input.display(), error);
+    }
+    if errors.len() > shown {
+        let hidden = errors.len() - shown;
+        log!(level, "{}: {} more errors", input.display(), hidden);
+    }
+}
 
-/// Writes a payment line in ledger.
-fn pay(buf: &mut dyn Write, acc: &str, sign: &str, amt: &str, cmt: &str) -> io::Result<()> {
-    let indent_width = if INDENT == "\t" { 8 } else { INDENT.len() };
-    let pad = LINE_WIDTH - indent_width - acc.len() - sign.len() - amt.len() - 1;
+/// Prints parsed rows of a statement as a table.
+fn print_rows(input: &Path, txns: &[Transaction]) {
+    let title = txns.iter().map(|txn| txn.title.len()).max().unwrap_or(0);
+    let comment = txns.iter().map(|txn| txn.comment.len()).max().unwrap_or(0);
+    let (title, comment) = (title.max("title".len()), comment.max("comment".len()));
+    eprintln!("{}:", input.display());
+    eprintln!(
+        "{:10}  {:title$}  {:comment$}  {:>12}  {:>12}  {:>12}",
+        "date",
+        "title",
+        "comment",
+        "debit",
+        "credit",
+        "total",
+        title = title,
+        comment = comment
+    );
+    for txn in txns {
+        eprintln!(
+            "{:10}  {:title$}  {:comment$}  {:>12}  {:>12}  {:>12}",
+            txn.date.to_string(),
+            txn.title,
+            txn.comment,
+            amount::format(txn.debit),
+            amount::format(txn.credit),
+            amount::format(txn.total),
+            title = title,
+            comment = comment
+        );
+    }
+}
 
-    writeln!(
-        buf,
-        "{}{}{:pad$} {}{} {}  ; {}",
-        INDENT,
-        acc,
-        "",
-        sign,
-        amt,
-        COMMODITY,
-        cmt,
-        pad = pad
-    )
+/// Checks that hledger reads the entries as ledger, skipped if it is missing.
+fn hledger_check(entries: &[Entry], config: &Config) -> fs_ledger::Result<()> {
+    let mut out = Vec::new();
+    fs_ledger::write_entries(&mut out, entries, &mut Ledger, config)?;
+    let child = Command::new("hledger")
+        .args(["-f", "-", "balance"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            warn!("hledger not found, skipping --validate");
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    // hledger reads all of stdin before it reports anything
+    child.stdin.take().unwrap().write_all(&out)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(LedgerError::Validation(stderr.trim().to_owned()));
+    }
+    Ok(())
+}
+
+/// Logs to stderr, warnings by default or errors only if quiet, `RUST_LOG`
+/// overrides both.
+fn init_logger(quiet: bool) {
+    let level = if quiet { "error" } else { "warn" };
+    env_logger::Builder::from_env(Env::default().default_filter_or(level))
+        .format(|buf, record| match record.level() {
+            Level::Error => writeln!(buf, "fs-ledger: {}", record.args()),
+            Level::Warn => writeln!(buf, "fs-ledger: warning: {}", record.args()),
+            level => writeln!(
+                buf,
+                "fs-ledger: {}: {}",
+                level.as_str().to_lowercase(),
+                record.args()
+            ),
+        })
+        .init();
 }
 
-fn main() -> io::Result<()> {
-    // pre-2022 uses `| |`, after that it uses `||`
-    let re = Regex::new(r"\A ([0-9]{4}-[0-9]{2}-[0-9]{2})  (.*?)(?: \| ?\| (.+?))?  \(([[0-9],]+\.[0-9]{2})\)  ([[0-9],]+\.[0-9]{2})  ([[0-9],]+\.[0-9]{2}) ").unwrap();
+fn main() {
+    if let Err(e) = run() {
+        error!("{}", e);
+        process::exit(1);
+    }
+}
 
-    // argument parsing
-    let mut args = env::args().skip(1);
-    let input = args.next().expect("Input file requried");
-    let (mut stdout, mut fsout);
-    let buf: &mut dyn Write = if let Some(output) = args.next() {
+/// Parse funding societies account statement into plain text ledger.
+#[derive(Parser)]
+#[command(version, about, args_override_self = true)]
+struct Args {
+    /// Statements, quoted glob patterns are expanded
+    #[arg(required = true, value_name = "INPUT")]
+    inputs: Vec<String>,
+    /// Write to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Append rows after the last date in a ledger file to it
+    #[arg(long, value_name = "LEDGER", conflicts_with = "output")]
+    append: Option<PathBuf>,
+    /// Overwrite the output file if it exists
+    #[arg(long)]
+    force: bool,
+    /// Accounts and options in toml
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Rules in toml tried before the config
+    #[arg(long)]
+    rules: Option<PathBuf>,
+    /// Commodity, overrides the statement and the config
+    #[arg(long)]
+    commodity: Option<String>,
+    /// Indentation, tab or a number of spaces
+    #[arg(long)]
+    indent: Option<Indent>,
+    /// Column amounts are aligned to
+    #[arg(long)]
+    width: Option<usize>,
+    /// Align amounts to the width or to the longest posting
+    #[arg(long, value_enum, default_value_t = Align::Fixed)]
+    align: Align,
+    /// Negative amounts as minus or paren
+    #[arg(long)]
+    negative: Option<Negative>,
+    /// Separator before the cents
+    #[arg(long, value_name = "SEP")]
+    decimal_sep: Option<String>,
+    /// Separator of thousands, empty for none
+    #[arg(long, value_name = "SEP")]
+    thousands_sep: Option<String>,
+    /// Line endings of the output
+    #[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+    line_ending: LineEnding,
+    /// ledger, beancount, csv, json, qif or ofx
+    #[arg(long, default_value = "ledger")]
+    format: OutputFormat,
+    /// Order of ambiguous dates, dmy or mdy
+    #[arg(long)]
+    date_order: Option<DateOrder>,
+    /// Split service fees into the fee and the tax included at the rate
+    #[arg(long, value_name = "PERCENT")]
+    sst_rate: Option<Decimal>,
+    /// Check that hledger reads the output, if it is installed
+    #[arg(long)]
+    validate: bool,
+    /// Check the running balance of each row
+    #[arg(long)]
+    verify: bool,
+    /// Fail on unknown rows
+    #[arg(long)]
+    strict: bool,
+    /// Warn instead of failing when pdftotext reports errors
+    #[arg(long)]
+    allow_errors: bool,
+    /// Write a comment when the page of the statement changes
+    #[arg(long)]
+    page_comments: bool,
+    /// Number of pdftotext errors shown, 0 for all
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    max_errors: usize,
+    /// Post to a subaccount of funds for each loan
+    #[arg(long)]
+    per_loan: bool,
+    /// Write the running balance after each posting comment
+    #[arg(long)]
+    inline_balance: bool,
+    /// Tag entries with their loan id
+    #[arg(long)]
+    loan_tag: bool,
+    /// Assert the balance before the first entry
+    #[arg(long)]
+    opening_balance: bool,
+    /// Assert the balance after the last row of each statement
+    #[arg(long)]
+    balance_assertions: bool,
+    /// Declare the commodity before the entries
+    #[arg(long)]
+    declare_commodity: bool,
+    /// Flag of all entries, *, ! or none
+    #[arg(long, conflicts_with_all = ["all_pending", "all_cleared"])]
+    flag: Option<Flag>,
+    /// Flag all entries pending with `!`
+    #[arg(long, conflicts_with = "all_cleared")]
+    all_pending: bool,
+    /// Flag all entries cleared with `*`
+    #[arg(long)]
+    all_cleared: bool,
+    /// Sort rows of all statements by date
+    #[arg(long)]
+    sort: bool,
+    /// Print totals of each account to stderr
+    #[arg(long)]
+    summary: bool,
+    /// Print the number of postings of each kind instead of writing them
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+    /// Print the comments of unknown rows with their count instead of writing them
+    #[arg(long, conflicts_with = "dry_run")]
+    list_unknown: bool,
+    /// Print the parsed rows of each statement to stderr
+    #[arg(long)]
+    debug_rows: bool,
+    /// Silence warnings
+    #[arg(short, long)]
+    quiet: bool,
+    /// Statements read at once, defaults to the number of cpus
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+    /// Drop rows repeated in overlapping statements
+    #[arg(long)]
+    dedup: bool,
+    /// Skip transactions whose net amount is smaller
+    #[arg(long, value_name = "AMOUNT")]
+    min_amount: Option<Decimal>,
+    /// Skip rows before the date
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    from: Option<NaiveDate>,
+    /// Skip rows after the date
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    to: Option<NaiveDate>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Align {
+    Fixed,
+    Auto,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+fn run() -> fs_ledger::Result<()> {
+    let Args {
+        inputs: mut positional,
+        mut output,
+        append,
+        force,
+        config: config_path,
+        rules: rules_path,
+        commodity,
+        indent,
+        width,
+        align,
+        negative,
+        decimal_sep,
+        thousands_sep,
+        line_ending,
+        format,
+        date_order,
+        sst_rate,
+        validate,
+        verify,
+        strict,
+        allow_errors,
+        page_comments,
+        max_errors,
+        per_loan,
+        loan_tag,
+        inline_balance,
+        opening_balance,
+        balance_assertions,
+        declare_commodity,
+        flag,
+        all_pending,
+        all_cleared,
+        sort,
+        summary,
+        dry_run,
+        list_unknown,
+        debug_rows,
+        quiet,
+        jobs,
+        dedup,
+        min_amount,
+        from,
+        to,
+    } = Args::parse();
+    init_logger(quiet);
+    let pending = (all_pending || all_cleared).then_some(all_pending);
+    let filtered = min_amount.is_some() || from.is_some() || to.is_some();
+    let (from, to) = (from.unwrap_or(NaiveDate::MIN), to.unwrap_or(NaiveDate::MAX));
+    // `fs-ledger input.pdf output.ledger` without `-o` still works
+    if output.is_none() && positional.len() > 1 {
+        let last = positional.last().unwrap().to_lowercase();
+        if !last.ends_with(".pdf") && !last.ends_with(".txt") && last != "-" {
+            output = positional.pop();
+        }
+    }
+    let inputs = expand_inputs(positional)?;
+    let mut config = match config_path {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+    if let Some(path) = rules_path {
+        config.load_rules(path)?;
+    }
+    let forced_commodity = commodity.is_some();
+    if forced_commodity {
+        config.commodity = commodity;
+    }
+    if let Some(indent) = indent {
+        config.indent = indent;
+    }
+    if let Some(width) = width {
+        config.width = width;
+    }
+    if let Some(negative) = negative {
+        config.negative = negative;
+    }
+    if let Some(sep) = decimal_sep {
+        config.decimal_sep = sep;
+    }
+    if let Some(sep) = thousands_sep {
+        config.thousands_sep = sep;
+    }
+    if let Some(date_order) = date_order {
+        config.date_order = date_order;
+    }
+    if sst_rate.is_some() {
+        config.sst_rate = sst_rate;
+    }
+    if let Some(rate) = config.sst_rate.filter(|rate| *rate < Decimal::ZERO) {
+        let msg = format!("sst rate {} cannot be negative", rate);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg).into());
+    }
+    // unknown rows are listed rather than failing
+    config.strict = (config.strict || strict) && !list_unknown;
+    config.allow_errors |= allow_errors;
+    config.page_comments |= page_comments;
+    config.per_loan |= per_loan;
+    config.loan_tag |= loan_tag;
+    config.inline_balance |= inline_balance;
+    config.declare_commodity |= declare_commodity;
+    config.opening_balance |= opening_balance;
+    // both read (1.00) as an expression, which flips the sign
+    if matches!(config.negative, Negative::Paren)
+        && matches!(format, OutputFormat::Ledger | OutputFormat::Beancount)
+    {
+        let msg = "negative paren is read as positive by ledger and beancount";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg).into());
+    }
+    // balances are left as in the statement, they would not add up
+    if filtered && (balance_assertions || config.opening_balance) {
+        let msg = "--min-amount, --from and --to cannot be used with --balance-assertions \
+                   or --opening-balance";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg).into());
+    }
+    if let Some(pending) = pending {
+        config.pending = pending;
+        config.flag = None;
+    }
+    if flag.is_some() {
+        config.flag = flag;
+    }
+    if config.width < config.min_width() {
+        warn!(
+            "width {} too small for the accounts, need at least {}",
+            config.width,
+            config.min_width()
+        );
+    }
+    // other formats cannot be continued by writing more after them
+    if append.is_some() && !matches!(format, OutputFormat::Ledger) {
+        let msg = "--append only works with ledger output";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg).into());
+    }
+    // read before the statements so new rows can be picked out
+    let existing = match &append {
+        Some(path) => Some(fs::read_to_string(path)?),
+        None => None,
+    };
+    let after = existing
+        .as_deref()
+        .and_then(|src| fs_ledger::last_date(src, config.date_order));
+    // the commodities are already declared in the ledger
+    if append.is_some() {
+        config.declare_commodity = false;
+    }
+    let (mut stdout, mut fsout, mut sink);
+    let buf: &mut dyn Write = if dry_run || list_unknown {
+        sink = io::sink();
+        &mut sink
+    } else if let Some(path) = &append {
+        fsout = BufWriter::new(OpenOptions::new().append(true).open(path)?);
+        &mut fsout
+    } else if let Some(output) = output {
+        // devices like /dev/null are fine to write to
+        if !force && Path::new(&output).is_file() {
+            let msg = format!("{} already exists, use --force to overwrite it", output);
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, msg).into());
+        }
         fsout = BufWriter::new(File::create(output)?);
         &mut fsout
     } else {
         stdout = BufWriter::new(io::stdout());
         &mut stdout
     };
+    let mut crlf_buf;
+    let buf: &mut dyn Write = if line_ending == LineEnding::Crlf {
+        crlf_buf = Crlf(buf);
+        &mut crlf_buf
+    } else {
+        buf
+    };
+    // the first header goes on its own line
+    if existing.is_some_and(|src| !src.is_empty() && !src.ends_with('\n')) {
+        writeln!(buf)?;
+    }
 
-    // TODO: use something else since hyphenation is broken in some cases
-    // parse pdf into text
-    let output = Command::new("pdftotext")
-        .args(["-nopgbrk", &input, "-"])
-        .output()?;
-    let stderr = String::from_utf8(output.stderr).unwrap();
-    assert!(output.status.success(), "{}", stderr);
-    let src = String::from_utf8(output.stdout).expect("Fail to decode output");
-    let mut src = &src[..];
-
-    // take only table
-    // pre-2022 uses `Balance (RM)\n`, after that it uses `Balance\n(RM)\n`
-    src = Regex::new(r"Balance[\n ]\(RM\)\n")
-        .unwrap()
-        .splitn(src, 2)
-        .nth(1)
-        .expect("Cannot split table start");
-    src = src
-        .rsplit_once("\nImportant!\n")
-        .map(|x| x.0)
-        .expect("Cannot split table end");
-
-    // 2023 fix broken page break on description
-    // From Early Payment\n\n(0.00)\n\n0.05\n\n140.45\n\nFee\n2023-01-10
-    //   to Early Payment Fee\n\n(0.00)\n\n0.05\n\n140.45\n\n2023-01-10
-    let desc_re = Regex::new(r"(.*?(?: \| ?\| [^0-9]+))?\n\n(\([[0-9],]+\.[0-9]{2}\)\n\n[[0-9],]+\.[0-9]{2}\n\n[[0-9],]+\.[0-9]{2})\n\n([^0-9]+)\n([0-9]{4}-[0-9]{2}-[0-9]{2})\n").unwrap();
-    let src = &desc_re.replace_all(src, "$1 $3\n\n$2\n\n$4\n");
-
-    // convert to single line, sometimes newline appear in middle
-    let src = src.replace('\n', " ");
-    let mut src = &src[..];
-
-    // parse and output ledger
-    while let Some(mut cap) = re.captures(src) {
-        src = &src[cap[0].len()..];
-        let mut comment = "";
-        let title = if &cap[2] == "Deposit" || cap[2].starts_with("Withdrawal") {
-            if let Some(cmt) = cap.get(3) {
-                comment = cmt.as_str();
-            }
-            "Funding Societies"
-        } else if cap[2].contains("invested") {
-            // Auto Investment: invested 100 into XXXX-00000000
-            cap[2].rsplit("into ").next().unwrap()
-        } else if cap[2].ends_with("repayment)") {
-            // XXXX-00000000 (1 of 1 repayment)
-            let mut parts = cap[2].rsplitn(2, " (");
-            comment = parts.next().unwrap().trim_end_matches(')');
-            parts.next().unwrap().trim_start_matches("Revert ")
-        } else {
-            cap[2].trim_start_matches("Revert ")
-        };
-        write!(buf, "{} * {}", &cap[1], title)?;
-        if !comment.is_empty() {
-            writeln!(buf, "  ; {}", comment)?;
-        } else {
-            writeln!(buf)?;
-        }
-        writeln!(buf, "{}{}", INDENT, ASSET)?;
-        if &cap[5] == "0.00" && cap[2].contains("invested") {
-            let cmt = cap[2].split(": ").next().unwrap();
-            pay(buf, FUNDS, "", &cap[4], cmt)?;
-        } else if &cap[2] == "Deposit" {
-            pay(buf, BANK, "-", &cap[5], &cap[2])?;
-        } else if cap[2].starts_with("Withdrawal") {
-            pay(buf, BANK, "", &cap[4], &cap[2])?;
-        } else if cap[2].starts_with("Adjustment for investment to ") {
-            assert_eq!(&cap[4], "0.00", "Only negative adjustment supported");
-            pay(buf, FUNDS, "-", &cap[5], "Adjustment")?;
-        } else {
-            // parse multiple lines of payment for the same transaction
-            loop {
-                match (&cap[3], &cap[4], &cap[5]) {
-                    (cmt @ "Service Fee", amt, "0.00") => pay(buf, EXPENSE, "", amt, cmt)?,
-                    (cmt @ "Service Fee", "0.00", amt) => pay(buf, EXPENSE, "-", amt, cmt)?, // revert
-                    (cmt @ "Interest", amt, "0.00") => pay(buf, INCOME, "", amt, cmt)?, // revert
-                    (cmt @ "Interest", "0.00", amt) => pay(buf, INCOME, "-", amt, cmt)?,
-                    (cmt @ "Early Payment Fee", "0.00", amt) => pay(buf, INCOME, "-", amt, cmt)?,
-                    (cmt @ "Late Interest Fee", "0.00", amt) => pay(buf, INCOME, "-", amt, cmt)?,
-                    (cmt @ "Returns", "0.00", amt) => pay(buf, INCOME, "-", amt, cmt)?, // revert
-                    (cmt @ "Late Returns Fee", "0.00", amt) => pay(buf, INCOME, "-", amt, cmt)?, // revert
-                    (cmt @ "Principal", amt, "0.00") => pay(buf, FUNDS, "", amt, cmt)?, // revert
-                    (cmt @ "Principal", "0.00", amt) => pay(buf, FUNDS, "-", amt, cmt)?,
-                    (_, dr, cr) => unimplemented!("{} - {} {} {}", &cap[2], &cap[3], dr, cr),
-                }
-                if let Some(ncap) = re.captures(src) {
-                    if cap[1] == ncap[1] && cap[2] == ncap[2] {
-                        cap = ncap;
-                        src = &src[cap[0].len()..];
-                        continue;
+    let mut txns = Vec::new();
+    let mut mismatches = 0;
+    let mut ends = Vec::new();
+    let mut ranges = Vec::new();
+    let mut starts = Vec::new();
+    let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let statements = fs_ledger::read_statements(&inputs, &config, jobs);
+    for (input, statement) in inputs.iter().zip(statements) {
+        let mut statement = match statement {
+            Ok(statement) => statement,
+            Err(e) => {
+                if let LedgerError::Statement { source, .. } = &e {
+                    if let LedgerError::Extraction(errors) = &**source {
+                        report_errors(input, errors, max_errors, Level::Error);
                     }
                 }
-                break;
+                return Err(e);
+            }
+        };
+        // currency given in the statement beats the config, only the flag overrides it
+        if forced_commodity {
+            for txn in &mut statement.transactions {
+                txn.commodity = None;
             }
         }
-        // separate transactions with empty line
-        writeln!(buf)?;
-        #[cfg(debug_assertions)]
-        buf.flush()?;
+        report_errors(input, &statement.errors, max_errors, Level::Warn);
+        if statement.transactions.is_empty() {
+            warn!("{}: no transactions", input.display());
+        }
+        if debug_rows {
+            print_rows(input, &statement.transactions);
+        }
+        if verify {
+            for m in fs_ledger::verify(&statement.transactions) {
+                error!(
+                    "{}: {} {:?} {:?} balance is {} but computed {}",
+                    input.display(),
+                    m.txn.date,
+                    m.txn.title,
+                    m.txn.comment,
+                    m.txn.total,
+                    m.expected
+                );
+                mismatches += 1;
+            }
+        }
+        if let (Some(period), Some(first)) = (statement.period, statement.transactions.first()) {
+            starts.push((first.clone(), period));
+        }
+        ends.extend(statement.transactions.last().cloned());
+        if let (Some(first), Some(last)) = (
+            statement.transactions.first(),
+            statement.transactions.last(),
+        ) {
+            ranges.push((input, (first.date, last.date)));
+        }
+        txns.extend(statement.transactions);
+    }
+    let dates: Vec<_> = ranges.iter().map(|(_, dates)| *dates).collect();
+    for period in fs_ledger::check_periods(&dates) {
+        let (kind, a, b) = match period {
+            Period::Overlap(a, b) => ("overlaps", a, b),
+            Period::Gap(a, b) => ("leaves a gap before", a, b),
+        };
+        let ((a, (_, end)), (b, (start, _))) = (ranges[a], ranges[b]);
+        warn!(
+            "{} ending {} {} {} starting {}",
+            a.display(),
+            end,
+            kind,
+            b.display(),
+            start
+        );
+    }
+    if dedup {
+        fs_ledger::dedup(&mut txns);
+    }
+    // stable so rows of the same day stay in statement order
+    if sort {
+        txns.sort_by_key(|txn| txn.date);
+    }
+    txns.retain(|txn| (from..=to).contains(&txn.date));
+    if let Some(after) = after {
+        txns.retain(|txn| txn.date > after);
+    }
+    let mut entries = fs_ledger::entries(&txns, &config)?;
+    // whole transactions by their net amount, so fees stay with their repayment
+    if let Some(min) = min_amount {
+        entries.retain(|entry| {
+            let net: Decimal = entry.postings.iter().map(|p| p.amount).sum();
+            net.abs() >= min
+        });
+    }
+    if balance_assertions {
+        fs_ledger::mark_closing(&mut entries, &ends);
+    }
+    // entries borrow the config, the aligned width goes to a copy
+    let aligned = (align == Align::Auto).then(|| Config {
+        width: fs_ledger::auto_width(&entries, &config),
+        ..config.clone()
+    });
+    let config = aligned.as_ref().unwrap_or(&config);
+    let unknown = fs_ledger::unknown_comments(&entries, config);
+    if list_unknown {
+        for (cmt, n) in &unknown {
+            println!("{:6}  {}", n, cmt);
+        }
+        return Ok(());
+    }
+    if !unknown.is_empty() {
+        let counts: Vec<_> = unknown
+            .iter()
+            .map(|(cmt, n)| format!("{:?} ({}x)", cmt, n))
+            .collect();
+        warn!(
+            "{} unrecognized comment types posted to {}: {}",
+            unknown.len(),
+            config.unknown,
+            counts.join(", ")
+        );
+    }
+    fs_ledger::mark_periods(&mut entries, &starts);
+    let mut formatter = format.formatter();
+    fs_ledger::write_entries(buf, &entries, &mut *formatter, config)?;
+    if validate {
+        match format {
+            OutputFormat::Ledger => hledger_check(&entries, config)?,
+            _ => warn!("--validate only checks ledger output, skipped"),
+        }
+    }
+    if summary {
+        let totals = fs_ledger::totals(&entries);
+        let width = totals.keys().map(|acc| acc.len()).max().unwrap_or(0);
+        let width = width.max("ending balance".len());
+        for (acc, total) in totals {
+            eprintln!(
+                "{:width$}  {:>14}",
+                acc,
+                amount::format(total),
+                width = width
+            );
+        }
+        if let Some(txn) = txns.last() {
+            let total = amount::format(txn.total);
+            eprintln!("{:width$}  {:>14}", "ending balance", total, width = width);
+        }
+    }
+    if dry_run {
+        let mut kinds = BTreeMap::new();
+        for p in entries.iter().flat_map(|entry| &entry.postings) {
+            *kinds.entry(p.comment).or_insert(0) += 1;
+        }
+        let width = kinds.keys().map(|kind| kind.len()).max().unwrap_or(0);
+        for (kind, n) in kinds {
+            eprintln!("{:width$}  {:>6}", kind, n, width = width);
+        }
+        let dates = txns.iter().map(|txn| txn.date);
+        if let (Some(first), Some(last)) = (dates.clone().min(), dates.max()) {
+            eprintln!("{} rows from {} to {}", txns.len(), first, last);
+        }
+        if !unknown.is_empty() {
+            return Err(LedgerError::UnknownComments(unknown.values().sum()));
+        }
+    }
+    if mismatches > 0 {
+        return Err(fs_ledger::LedgerError::BalanceMismatch(mismatches));
     }
-    assert_eq!(src, "", "parsing stopped halfway");
-
     Ok(())
 }
@@ -0,0 +1,83 @@
+//! Reads back the ledger written by [`Ledger`](crate::Ledger).
+use crate::{amount, date, Config, LedgerError, Result, Transaction};
+use regex::Regex;
+use rust_decimal::Decimal;
+
+/// Parses an amount written with a minus or in parentheses.
+fn signed(s: &str, config: &Config) -> Result<Decimal> {
+    let parse = |s| amount::parse_in(s, &config.decimal_sep, &config.thousands_sep);
+    match s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        Some(s) => parse(s).map(|amt| -amt),
+        None => parse(s),
+    }
+}
+
+/// Reads the ledger written by this crate back into transactions, one for
+/// each posting with an amount. Other ledgers are not supported.
+///
+/// The comments and amounts are read as written with `config`.
+///
+/// Titles are not kept in the ledger, `title` is the payee and `comment` is
+/// the posting comment. `total` is the running balance from zero, or from the
+/// balance asserted by `opening_balance` and `closing`.
+pub fn read_ledger(src: &str, config: &Config) -> Result<Vec<Transaction>> {
+    let comment = format!(
+        "{}{}",
+        " ".repeat(config.comment_gap),
+        regex::escape(&config.comment_prefix)
+    );
+    let header = Regex::new(&format!(r"\A([^ \t]+)(?: [*!])? (.*?)(?:{}.*)?\z", comment)).unwrap();
+    let assertion = Regex::new(r"  +(?:[^ \t]+ [^ \t]+ )?= ([^ \t]+) [^ \t]+\z").unwrap();
+    // the commodity is lazy for comments without a gap
+    let posting = Regex::new(&format!(
+        r"\A[ \t]+[^ \t].*?  +([^ \t]+) [^ \t]+?(?:{}(.*))?\z",
+        comment
+    ))
+    .unwrap();
+    let prefix = config.comment_prefix.trim();
+    let mut txns = Vec::new();
+    let mut entry = None;
+    let mut total = Decimal::ZERO;
+    for (i, line) in src.lines().enumerate() {
+        let malformed = || LedgerError::MalformedLedger {
+            line: i + 1,
+            near: line.to_owned(),
+        };
+        let trimmed = line.trim_start();
+        let is_comment =
+            trimmed.starts_with(';') || !prefix.is_empty() && trimmed.starts_with(prefix);
+        if trimmed.is_empty() || is_comment || line.starts_with("commodity ") {
+            continue;
+        }
+        if trimmed.len() == line.len() {
+            let cap = header.captures(line).ok_or_else(malformed)?;
+            // ledger allows an auxiliary date like `2020-01-31=2020-02-01`
+            let date = date::parse(cap[1].split('=').next().unwrap(), config.date_order)?;
+            entry = Some((date, cap[2].to_owned()));
+        } else if let Some(cap) = assertion.captures(line) {
+            total = signed(&cap[1], config)?;
+        } else if let Some(cap) = posting.captures(line) {
+            let (date, payee) = entry.as_ref().ok_or_else(malformed)?;
+            let amt = signed(&cap[1], config)?;
+            let (debit, credit) = if amt.is_sign_negative() {
+                (Decimal::ZERO, -amt)
+            } else {
+                (amt, Decimal::ZERO)
+            };
+            total += credit - debit;
+            txns.push(Transaction {
+                date: *date,
+                title: payee.clone(),
+                comment: cap.get(2).map_or("", |m| m.as_str()).to_owned(),
+                debit,
+                credit,
+                total,
+                page: None,
+                commodity: None,
+            });
+        } else if trimmed.contains("  ") {
+            return Err(malformed());
+        }
+    }
+    Ok(txns)
+}
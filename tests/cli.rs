@@ -0,0 +1,171 @@
+//! Runs the fs-ledger binary on the fixtures.
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn fs_ledger() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_fs-ledger"))
+}
+
+/// Empty directory for the outputs of a test.
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fs-ledger-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn last_output_wins() {
+    let dir = temp_dir("last_output_wins");
+    let (a, b) = (dir.join("a.ledger"), dir.join("b.ledger"));
+    let status = fs_ledger()
+        .arg("tests/fixtures/statement.txt")
+        .arg("-o")
+        .arg(&a)
+        .arg("-o")
+        .arg(&b)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(!a.exists());
+    let expected = fs::read_to_string("tests/fixtures/statement.ledger").unwrap();
+    assert_eq!(fs::read_to_string(&b).unwrap(), expected);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn pdftotext_warnings() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = temp_dir("pdftotext_warnings");
+    // pdftotext which prints the text given as the pdf with a message
+    let script = dir.join("pdftotext");
+    let fake = "#!/bin/sh\n\
+                echo \"$MESSAGE\" >&2\n\
+                for a; do case $a in -*) ;; *) f=$a;; esac; done\n\
+                cat \"$f\"\n";
+    fs::write(&script, fake).unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    let pdf = dir.join("statement.pdf");
+    fs::copy("tests/fixtures/statement.txt", &pdf).unwrap();
+    let path = format!("{}:{}", dir.display(), std::env::var("PATH").unwrap());
+    let run = |message: &str| {
+        fs_ledger()
+            .arg(&pdf)
+            .arg("-o")
+            .arg(dir.join("out.ledger"))
+            .arg("--force")
+            .env("PATH", &path)
+            .env("MESSAGE", message)
+            .output()
+            .unwrap()
+    };
+    let output = run("Syntax Warning: Invalid Font Weight");
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("warning: ") && stderr.contains("Syntax Warning: Invalid Font Weight"));
+    let output = run("Syntax Error (42): Illegal character");
+    assert!(!output.status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn min_amount_keeps_transactions() {
+    let output = fs_ledger()
+        .args(["tests/fixtures/statement.txt", "--min-amount", "50"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let out = String::from_utf8(output.stdout).unwrap();
+    assert!(!out.contains("Withdrawal"));
+    // interest and fees are smaller but stay with their repayment
+    assert!(out.contains("; Interest\n"));
+    assert!(out.contains("; Service Fee\n"));
+}
+
+#[test]
+fn min_amount_with_balance_assertions() {
+    let output = fs_ledger()
+        .args(["tests/fixtures/statement.txt", "--min-amount", "50"])
+        .arg("--balance-assertions")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--balance-assertions"));
+}
+
+#[test]
+fn negative_paren_rejected() {
+    for format in ["ledger", "beancount"] {
+        let output = fs_ledger()
+            .args(["tests/fixtures/statement.txt", "--negative", "paren"])
+            .args(["--format", format])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("paren"));
+    }
+}
+
+#[test]
+fn append_without_newline() {
+    let dir = temp_dir("append_without_newline");
+    let path = dir.join("fs.ledger");
+    let ledger = "commodity MYR\n\n\
+                  2020-01-01 * Funding Societies\n\
+                  \tassets:bank:pbe  -200.00 MYR\n\
+                  \tequity";
+    fs::write(&path, ledger).unwrap();
+    let status = fs_ledger()
+        .arg("tests/fixtures/statement.txt")
+        .arg("--append")
+        .arg(&path)
+        .arg("--declare-commodity")
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let out = fs::read_to_string(&path).unwrap();
+    assert!(out.contains("\tequity\n2020-01-02 * XXXX-00000000\n"));
+    assert_eq!(out.matches("commodity MYR").count(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn append_only_ledger() {
+    let dir = temp_dir("append_only_ledger");
+    let path = dir.join("fs.bean");
+    fs::write(&path, "").unwrap();
+    let status = fs_ledger()
+        .arg("tests/fixtures/statement.txt")
+        .arg("--append")
+        .arg(&path)
+        .args(["--format", "beancount"])
+        .status()
+        .unwrap();
+    assert!(!status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn negative_sst_rate() {
+    let output = fs_ledger()
+        .args(["tests/fixtures/statement.txt", "--sst-rate=-100"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be negative"));
+}
+
+#[test]
+fn unknown_currency() {
+    let dir = temp_dir("unknown_currency");
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let txt = dir.join("statement.txt");
+    fs::write(&txt, src.replace("Balance (RM)", "Balance (€)")).unwrap();
+    let output = fs_ledger().arg(&txt).output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown currency \"€\""));
+    fs::remove_dir_all(&dir).unwrap();
+}
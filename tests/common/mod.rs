@@ -0,0 +1,15 @@
+//! Helpers shared by the tests.
+use fs_ledger::{Config, Entry, Format, Ledger, Transaction};
+
+/// Writes entries with the format.
+pub fn write(entries: &[Entry], format: &mut dyn Format, config: &Config) -> String {
+    let mut buf = Vec::new();
+    fs_ledger::write_entries(&mut buf, entries, format, config).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+/// Classifies rows into entries and writes them as ledger.
+pub fn ledger(txns: &[Transaction], config: &Config) -> String {
+    let entries = fs_ledger::entries(txns, config).unwrap();
+    write(&entries, &mut Ledger, config)
+}
@@ -0,0 +1,856 @@
+mod common;
+
+use common::{ledger, write};
+use fs_ledger::{Config, Ledger, Transaction};
+use rust_decimal::Decimal;
+
+fn txn(date: &str, title: &str, comment: &str, debit: &str, credit: &str) -> Transaction {
+    Transaction {
+        date: date.parse().unwrap(),
+        title: title.to_owned(),
+        comment: comment.to_owned(),
+        debit: debit.parse().unwrap(),
+        credit: credit.parse().unwrap(),
+        total: Decimal::ZERO,
+        page: None,
+        commodity: None,
+    }
+}
+
+#[test]
+fn deposit() {
+    let txns = [txn(
+        "2020-01-01",
+        "Deposit",
+        "Funding Societies",
+        "0",
+        "200",
+    )];
+    assert_eq!(
+        ledger(&txns, &Config::default()),
+        "2020-01-01 * Funding Societies  ; Funding Societies\n\
+         \tassets:fundingsocieties\n\
+         \tassets:bank:pbe                                -200.00 MYR  ; Deposit\n\n"
+    );
+}
+
+#[test]
+fn withdrawal() {
+    let txns = [txn("2020-01-30", "Withdrawal", "For Name", "10", "0")];
+    assert_eq!(
+        ledger(&txns, &Config::default()),
+        "2020-01-30 * Funding Societies  ; For Name\n\
+         \tassets:fundingsocieties\n\
+         \tassets:bank:pbe                                  10.00 MYR  ; Withdrawal\n\n"
+    );
+}
+
+#[test]
+fn invested() {
+    let title = "Auto Investment: invested 100 into XXXX-00000000";
+    let txns = [txn("2020-01-02", title, "", "100", "0")];
+    assert_eq!(
+        ledger(&txns, &Config::default()),
+        "2020-01-02 * XXXX-00000000\n\
+         \tassets:fundingsocieties\n\
+         \tassets:funds:fundingsocieties                   100.00 MYR  ; Auto Investment\n\n"
+    );
+}
+
+#[test]
+fn repayment() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        txn("2020-01-31", title, "Principal", "0", "100"),
+        txn("2020-01-31", title, "Interest", "0", "1"),
+        txn("2020-01-31", title, "Service Fee", "0.20", "0"),
+    ];
+    assert_eq!(
+        ledger(&txns, &Config::default()),
+        "2020-01-31 * XXXX-00000000  ; 1 of 1 repayment\n\
+         \tassets:fundingsocieties\n\
+         \tassets:funds:fundingsocieties                  -100.00 MYR  ; Principal\n\
+         \tincome:interest                                  -1.00 MYR  ; Interest\n\
+         \texpenses:service                                  0.20 MYR  ; Service Fee\n\n"
+    );
+}
+
+#[test]
+fn revert_repayment() {
+    let title = "Revert XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        txn("2020-02-01", title, "Interest", "1", "0"),
+        txn("2020-02-01", title, "Service Fee", "0", "0.20"),
+    ];
+    assert_eq!(
+        ledger(&txns, &Config::default()),
+        "2020-02-01 * XXXX-00000000  ; 1 of 1 repayment\n\
+         \tassets:fundingsocieties\n\
+         \tincome:interest                                   1.00 MYR  ; Interest\n\
+         \texpenses:service                                 -0.20 MYR  ; Service Fee\n\n"
+    );
+}
+
+#[test]
+fn reversal_of_fee() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [txn(
+        "2020-02-01",
+        title,
+        "Reversal of Service Fee",
+        "0",
+        "0.20",
+    )];
+    assert_eq!(
+        ledger(&txns, &Config::default()),
+        "2020-02-01 * XXXX-00000000  ; 1 of 1 repayment\n\
+         \tassets:fundingsocieties\n\
+         \texpenses:service                                 -0.20 MYR  ; Reversal of Service Fee\n\n"
+    );
+}
+
+#[test]
+fn strict_unknown() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [txn("2020-01-31", title, "Mystery", "0", "1")];
+    let config = Config {
+        strict: true,
+        ..Config::default()
+    };
+    assert!(matches!(
+        fs_ledger::entries(&txns, &config),
+        Err(fs_ledger::LedgerError::UnknownTransaction(_))
+    ));
+}
+
+#[test]
+fn long_account() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [txn("2020-01-31", title, "Interest", "0", "1234567.89")];
+    let config = Config {
+        income: "income:interest:fundingsocieties:sme:very:deep:account".to_owned(),
+        ..Config::default()
+    };
+    assert_eq!(
+        ledger(&txns, &config),
+        "2020-01-31 * XXXX-00000000  ; 1 of 1 repayment\n\
+         \tassets:fundingsocieties\n\
+         \tincome:interest:fundingsocieties:sme:very:deep:account  -1,234,567.89 MYR  ; Interest\n\n"
+    );
+}
+
+#[test]
+fn unknown_comments() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        txn("2020-01-31", title, "Promo", "0", "1"),
+        txn("2020-01-31", title, "Interest", "0", "1"),
+        txn("2020-01-31", title, "Promo", "0", "2"),
+        txn("2020-02-29", title, "Adjustment Fee", "1", "0"),
+    ];
+    let config = Config::default();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let counts = fs_ledger::unknown_comments(&entries, &config);
+    let counts: Vec<_> = counts.into_iter().collect();
+    assert_eq!(counts, [("Adjustment Fee", 1), ("Promo", 2)]);
+}
+
+#[test]
+fn rules() {
+    let config: Config = toml::from_str(
+        r#"
+        [[rules]]
+        comment = "^Late .* Fee$"
+        account = "income:interest:late"
+
+        [[rules]]
+        comment = "Fee"
+        title = "^YYYY-"
+        account = "expenses:other"
+
+        [[rules]]
+        comment = "(?i)^early\\s+payment$"
+        title = "^ZZZZ-\\d+"
+        account = "income:interest:early"
+        "#,
+    )
+    .unwrap();
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    assert_eq!(
+        config.account(title, "Late Interest Fee"),
+        Some("income:interest:late")
+    );
+    // title does not match so the built-in comment is used
+    assert_eq!(
+        config.account(title, "Service Fee"),
+        Some("expenses:service")
+    );
+    let other = "YYYY-00000000 (1 of 1 repayment)";
+    assert_eq!(config.account(other, "Service Fee"), Some("expenses:other"));
+    assert_eq!(config.account(title, "Mystery"), None);
+    let early = "ZZZZ-00000000 (1 of 1 repayment)";
+    assert_eq!(
+        config.account(early, "Early Payment"),
+        Some("income:interest:early")
+    );
+}
+
+#[test]
+fn income_subaccounts() {
+    let config: Config = toml::from_str(
+        r#"
+        [comments]
+        "Late Interest Fee" = "income:interest:late"
+        "#,
+    )
+    .unwrap();
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    assert_eq!(
+        config.account(title, "Late Interest Fee"),
+        Some("income:interest:late")
+    );
+    assert_eq!(config.account(title, "Interest"), Some("income:interest"));
+}
+
+#[test]
+fn per_loan() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        txn("2020-01-31", title, "Principal", "0", "100"),
+        txn("2020-01-31", title, "Interest", "0", "1"),
+    ];
+    let config = Config {
+        per_loan: true,
+        ..Config::default()
+    };
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let accounts: Vec<_> = entries[0].postings.iter().map(|p| &*p.account).collect();
+    assert_eq!(
+        accounts,
+        [
+            "assets:funds:fundingsocieties:XXXX-00000000",
+            "income:interest"
+        ]
+    );
+}
+
+#[test]
+fn zero_posting() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        txn("2020-01-31", title, "Interest", "0", "1"),
+        txn("2020-01-31", title, "Service Fee", "0", "0"),
+    ];
+    assert_eq!(
+        ledger(&txns, &Config::default()),
+        "2020-01-31 * XXXX-00000000  ; 1 of 1 repayment\n\
+         \tassets:fundingsocieties\n\
+         \tincome:interest                                  -1.00 MYR  ; Interest\n\n"
+    );
+}
+
+#[test]
+fn auto_width() {
+    let txns = [txn("2020-01-30", "Withdrawal", "For Name", "1000", "0")];
+    let config = Config::default();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    // tab, assets:bank:pbe, two spaces and 1,000.00
+    assert_eq!(fs_ledger::auto_width(&entries, &config), 8 + 15 + 2 + 8);
+}
+
+#[test]
+fn crlf() {
+    use std::io::Write;
+    let mut buf = Vec::new();
+    write!(fs_ledger::Crlf(&mut buf), "a\nb\n\nc").unwrap();
+    assert_eq!(buf, b"a\r\nb\r\n\r\nc");
+}
+
+#[test]
+fn declare_commodity() {
+    let txns = [txn("2020-01-30", "Withdrawal", "For Name", "10", "0")];
+    let config = Config {
+        declare_commodity: true,
+        ..Config::default()
+    };
+    assert!(ledger(&txns, &config).starts_with("commodity MYR\n\n2020-01-30 * "));
+}
+
+#[test]
+fn totals() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        txn("2020-01-31", title, "Interest", "0", "1"),
+        txn("2020-01-31", title, "Service Fee", "0.20", "0"),
+        txn("2020-02-29", title, "Interest", "0", "2"),
+    ];
+    let config = Config::default();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let totals: Vec<_> = fs_ledger::totals(&entries)
+        .into_iter()
+        .map(|(acc, total)| (acc, total.to_string()))
+        .collect();
+    assert_eq!(
+        totals,
+        [
+            ("expenses:service", "0.20".to_owned()),
+            ("income:interest", "-3".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn last_date() {
+    let ledger = "; 2021-01-01 in a comment\n\
+                  2020-01-02 * XXXX-00000000\n\
+                  \tassets:fundingsocieties\n\n\
+                  2020/01/31=2020/02/01 * Funding Societies\n\
+                  \tassets:fundingsocieties\n";
+    let last = fs_ledger::last_date(ledger, Default::default());
+    assert_eq!(last, "2020-01-31".parse().ok());
+}
+
+#[test]
+fn loan_tag() {
+    let title = "Auto Investment: invested 100 into XXXX-00000000";
+    let txns = [txn("2020-01-02", title, "", "100", "0")];
+    let config = Config {
+        loan_tag: true,
+        ..Config::default()
+    };
+    assert!(
+        ledger(&txns, &config).starts_with("2020-01-02 * XXXX-00000000\n\t; loan: XXXX-00000000\n")
+    );
+}
+
+#[test]
+fn opening_balance() {
+    let txns = [Transaction {
+        total: "90".parse().unwrap(),
+        ..txn("2020-01-30", "Withdrawal", "For Name", "10", "0")
+    }];
+    let config = Config {
+        opening_balance: true,
+        ..Config::default()
+    };
+    let out = ledger(&txns, &config);
+    assert_eq!(
+        out.split_inclusive('\n').take(4).collect::<String>(),
+        "2020-01-30 * Opening Balance\n\
+         \tassets:fundingsocieties                       = 100.00 MYR\n\
+         \tequity:opening balances\n\n"
+    );
+}
+
+#[test]
+fn negative_paren() {
+    let title = "Revert XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        txn("2020-02-01", title, "Interest", "1", "0"),
+        txn("2020-02-01", title, "Service Fee", "0", "0.20"),
+    ];
+    let config = Config {
+        negative: fs_ledger::amount::Negative::Paren,
+        ..Config::default()
+    };
+    // the closing parenthesis takes the column of the last digit
+    assert_eq!(
+        ledger(&txns, &config),
+        "2020-02-01 * XXXX-00000000  ; 1 of 1 repayment\n\
+         \tassets:fundingsocieties\n\
+         \tincome:interest                                   1.00 MYR  ; Interest\n\
+         \texpenses:service                                (0.20) MYR  ; Service Fee\n\n"
+    );
+}
+
+#[test]
+fn repayment_without_space() {
+    let txns = [txn(
+        "2020-02-01",
+        "XXXX-00000000(1 of 1 repayment)",
+        "Interest",
+        "0",
+        "1",
+    )];
+    let config = Config::default();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    assert_eq!(entries[0].payee, "XXXX-00000000");
+    assert_eq!(entries[0].comment, "1 of 1 repayment");
+    let txns = [txn("2020-02-01", "repayment)", "Interest", "0", "1")];
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    assert_eq!(entries[0].payee, "repayment)");
+}
+
+#[test]
+fn closing_balance() {
+    let txns = [
+        Transaction {
+            total: "90".parse().unwrap(),
+            ..txn("2020-01-30", "Withdrawal", "For Name", "10", "0")
+        },
+        Transaction {
+            total: "85".parse().unwrap(),
+            ..txn("2020-01-31", "Withdrawal", "For Name", "5", "0")
+        },
+    ];
+    let config = Config::default();
+    let mut entries = fs_ledger::entries(&txns, &config).unwrap();
+    fs_ledger::mark_closing(&mut entries, &txns[1..]);
+    let out = write(&entries, &mut Ledger, &config);
+    assert_eq!(out.matches("Closing Balance").count(), 1);
+    assert!(out.ends_with(
+        "2020-01-31 * Closing Balance\n\
+         \tassets:fundingsocieties                  0 MYR = 85.00 MYR\n\n"
+    ));
+}
+
+#[test]
+fn statement_periods() {
+    let txns = [
+        txn("2020-01-30", "Withdrawal", "For Name", "10", "0"),
+        txn("2020-02-01", "Withdrawal", "For Name", "5", "0"),
+        txn("2020-02-02", "Deposit", "Funding Societies", "0", "200"),
+    ];
+    let day = |s: &str| s.parse().unwrap();
+    let starts = [
+        (txns[0].clone(), (day("2020-01-01"), day("2020-01-31"))),
+        (txns[1].clone(), (day("2020-02-01"), day("2020-02-29"))),
+    ];
+    let config = Config::default();
+    // the first row of the second statement is filtered out
+    let kept = [txns[0].clone(), txns[2].clone()];
+    let mut entries = fs_ledger::entries(&kept, &config).unwrap();
+    fs_ledger::mark_periods(&mut entries, &starts);
+    let out = write(&entries, &mut Ledger, &config);
+    assert!(out.starts_with("; Statement 2020-01-01 to 2020-01-31\n\n2020-01-30 * "));
+    assert!(out.contains("\n\n; Statement 2020-02-01 to 2020-02-29\n\n2020-02-02 * "));
+}
+
+#[test]
+fn closing_balance_zero_row() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        Transaction {
+            total: "90".parse().unwrap(),
+            ..txn("2020-01-30", "Withdrawal", "For Name", "10", "0")
+        },
+        Transaction {
+            total: "90".parse().unwrap(),
+            ..txn("2020-01-31", title, "Interest", "0", "0")
+        },
+    ];
+    let config = Config::default();
+    let mut entries = fs_ledger::entries(&txns, &config).unwrap();
+    fs_ledger::mark_closing(&mut entries, &txns[1..]);
+    let out = write(&entries, &mut Ledger, &config);
+    assert!(out.ends_with(
+        "2020-01-31 * Closing Balance\n\
+         \tassets:fundingsocieties                  0 MYR = 90.00 MYR\n\n"
+    ));
+}
+
+#[test]
+fn closing_balance_filtered_row() {
+    let txns = [
+        Transaction {
+            total: "90".parse().unwrap(),
+            ..txn("2020-01-30", "Withdrawal", "For Name", "10", "0")
+        },
+        Transaction {
+            total: "85".parse().unwrap(),
+            ..txn("2020-01-31", "Withdrawal", "For Name", "5", "0")
+        },
+    ];
+    let config = Config::default();
+    // the last row is filtered out but its balance is still asserted
+    let mut entries = fs_ledger::entries(&txns[..1], &config).unwrap();
+    fs_ledger::mark_closing(&mut entries, &txns[1..]);
+    let out = write(&entries, &mut Ledger, &config);
+    assert!(out.ends_with(
+        "2020-01-31 * Closing Balance\n\
+         \tassets:fundingsocieties                  0 MYR = 85.00 MYR\n\n"
+    ));
+}
+
+#[test]
+fn read_ledger_opening_balance() {
+    let txns = [
+        Transaction {
+            total: "90".parse().unwrap(),
+            ..txn("2020-01-30", "Withdrawal", "For Name", "10", "0")
+        },
+        Transaction {
+            total: "290".parse().unwrap(),
+            ..txn("2020-01-31", "Deposit", "Funding Societies", "0", "200")
+        },
+    ];
+    let config = Config {
+        opening_balance: true,
+        loan_tag: true,
+        negative: fs_ledger::amount::Negative::Paren,
+        ..Config::default()
+    };
+    let out = ledger(&txns, &config);
+    let read = fs_ledger::read_ledger(&out, &config).unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[0].total, txns[0].total);
+    assert_eq!(read[1].credit, txns[1].credit);
+    assert_eq!(read[1].total, txns[1].total);
+}
+
+#[test]
+fn read_ledger_malformed() {
+    let config = Config::default();
+    match fs_ledger::read_ledger("2020-01-01 * Payee\n\tassets  oops\n", &config) {
+        Err(fs_ledger::LedgerError::MalformedLedger { line, .. }) => assert_eq!(line, 2),
+        other => panic!("expected malformed ledger, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn qif() {
+    let txns = [txn(
+        "2020-01-01",
+        "Deposit",
+        "Funding Societies",
+        "0",
+        "200",
+    )];
+    let config = Config::default();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    assert_eq!(
+        write(&entries, &mut fs_ledger::Qif, &config),
+        "!Type:Bank\nD01/01/2020\nT200.00\nPFunding Societies\nMDeposit\nLassets:bank:pbe\n^\n"
+    );
+}
+
+#[test]
+fn ofx() {
+    let repayment = "XXXX-00000000 (1 of 1 repayment)";
+    let txns = [
+        txn("2020-01-31", repayment, "Principal", "0", "100"),
+        txn("2020-01-31", repayment, "Interest", "0", "1"),
+    ];
+    let config = Config::default();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let out = write(&entries, &mut fs_ledger::Ofx::default(), &config);
+    assert!(out.starts_with("OFXHEADER:100\n"));
+    assert!(out.contains("<TRNAMT>100.00\n<FITID>20200131-XXXX-00000000-1\n"));
+    assert!(out.contains("<FITID>20200131-XXXX-00000000-2\n<NAME>XXXX-00000000\n<MEMO>Interest\n"));
+    assert!(out.ends_with("</OFX>\n"));
+}
+
+#[test]
+fn sst_rate() {
+    let txns = [txn(
+        "2020-01-31",
+        "XXXX-00000000 (1 of 1 repayment)",
+        "Service Fee",
+        "1.06",
+        "0",
+    )];
+    let config = Config {
+        sst_rate: Some("6".parse().unwrap()),
+        ..Config::default()
+    };
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let amounts: Vec<_> = entries[0]
+        .postings
+        .iter()
+        .map(|p| (&*p.account, p.amount.to_string()))
+        .collect();
+    assert_eq!(
+        amounts,
+        [
+            ("expenses:service", "1.00".to_owned()),
+            ("expenses:tax", "0.06".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn classifier() {
+    struct ByLoan;
+    impl fs_ledger::Classifier for ByLoan {
+        fn account(&self, txn: &Transaction) -> Option<&str> {
+            txn.title.starts_with("XXXX-").then_some("income:xxxx")
+        }
+    }
+    let txns = [txn(
+        "2020-01-31",
+        "XXXX-00000000 (1 of 1 repayment)",
+        "Interest",
+        "0",
+        "1",
+    )];
+    let config = Config::default();
+    let entries = fs_ledger::entries_with(&txns, &config, &ByLoan).unwrap();
+    assert_eq!(entries[0].postings[0].account, "income:xxxx");
+}
+
+#[test]
+fn seven_figure_amount() {
+    let txns = [txn(
+        "2020-01-01",
+        "Deposit",
+        "Funding Societies",
+        "0",
+        "1234567.89",
+    )];
+    let config = Config {
+        width: 30,
+        ..Config::default()
+    };
+    // past the width it still has the two spaces ledger needs
+    assert!(ledger(&txns, &config).contains("\tassets:bank:pbe  -1,234,567.89 MYR  ; Deposit\n"));
+}
+
+#[test]
+fn inline_balance() {
+    let txns = [Transaction {
+        total: "1000".parse().unwrap(),
+        ..txn("2020-01-01", "Deposit", "Funding Societies", "0", "1000")
+    }];
+    let config = Config {
+        inline_balance: true,
+        ..Config::default()
+    };
+    assert!(ledger(&txns, &config).contains("MYR  ; Deposit; bal 1,000.00\n"));
+}
+
+#[test]
+fn comment_prefix() {
+    let txns = [txn("2020-01-30", "Withdrawal", "For Name", "10", "0")];
+    let config = Config {
+        comment_prefix: ";".to_owned(),
+        comment_gap: 1,
+        ..Config::default()
+    };
+    assert_eq!(
+        ledger(&txns, &config),
+        "2020-01-30 * Funding Societies ;For Name\n\
+         \tassets:fundingsocieties\n\
+         \tassets:bank:pbe                                  10.00 MYR ;Withdrawal\n\n"
+    );
+}
+
+#[test]
+fn read_ledger_config() {
+    let txns = [
+        txn("2020-01-30", "Withdrawal", "For Name", "1234.5", "0"),
+        txn("2020-01-31", "Deposit", "Funding Societies", "0", "2000"),
+    ];
+    let config = Config {
+        comment_prefix: "#".to_owned(),
+        comment_gap: 1,
+        decimal_sep: ",".to_owned(),
+        thousands_sep: ".".to_owned(),
+        ..Config::default()
+    };
+    let out = ledger(&txns, &config);
+    assert!(out.contains("1.234,50 MYR #Withdrawal\n"));
+    let read = fs_ledger::read_ledger(&out, &config).unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[0].title, "Funding Societies");
+    assert_eq!(read[0].comment, "Withdrawal");
+    assert_eq!(read[0].debit, "1234.5".parse().unwrap());
+    assert_eq!(read[1].credit, "2000".parse().unwrap());
+}
+
+#[test]
+fn check_periods() {
+    use fs_ledger::Period;
+    let date = |s: &str| s.parse().unwrap();
+    let ranges = [
+        (date("2020-03-02"), date("2020-03-30")),
+        (date("2020-01-01"), date("2020-01-31")),
+        (date("2020-01-15"), date("2020-02-28")),
+    ];
+    assert_eq!(fs_ledger::check_periods(&ranges), [Period::Overlap(1, 2)]);
+    let ranges = [ranges[0], ranges[1]];
+    assert_eq!(fs_ledger::check_periods(&ranges), [Period::Gap(1, 0)]);
+}
+
+#[test]
+fn no_flag() {
+    let txns = [txn("2020-01-30", "Withdrawal", "For Name", "10", "0")];
+    let config = Config {
+        flag: Some(fs_ledger::Flag::None),
+        pending: true,
+        ..Config::default()
+    };
+    let out = ledger(&txns, &config);
+    assert!(out.starts_with("2020-01-30 Funding Societies  ; For Name\n"));
+    let read = fs_ledger::read_ledger(&out, &config).unwrap();
+    assert_eq!(read[0].title, "Funding Societies");
+}
+
+#[test]
+fn deposit_with_both_columns() {
+    let txns = [txn(
+        "2020-01-01",
+        "Deposit",
+        "Funding Societies",
+        "5",
+        "200",
+    )];
+    let config = Config::default();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let amounts: Vec<_> = entries[0]
+        .postings
+        .iter()
+        .map(|p| p.amount.to_string())
+        .collect();
+    assert_eq!(amounts, ["-200", "5"]);
+}
+
+#[test]
+fn branches() {
+    let loan = "XXXX-00000000 (1 of 1 repayment)";
+    let adjustment = "Adjustment for investment to XXXX-00000000";
+    let cases = [
+        (
+            txn("2020-01-01", "Deposit", "Funding Societies", "0", "200"),
+            ("assets:bank:pbe", "-200", "Deposit"),
+        ),
+        (
+            txn("2020-01-30", "Withdrawal", "For Name", "10", "0"),
+            ("assets:bank:pbe", "10", "Withdrawal"),
+        ),
+        (
+            txn(
+                "2020-01-02",
+                "Auto Investment: invested 100 into XXXX-00000000",
+                "",
+                "100",
+                "0",
+            ),
+            ("assets:funds:fundingsocieties", "100", "Auto Investment"),
+        ),
+        (
+            txn("2020-02-01", adjustment, "", "0", "5"),
+            ("assets:funds:fundingsocieties", "-5", "Adjustment"),
+        ),
+        (
+            txn("2020-02-01", adjustment, "", "5", "0"),
+            ("expenses:unknown", "5", "Adjustment"),
+        ),
+        (
+            txn("2020-01-31", loan, "Principal", "0", "100"),
+            ("assets:funds:fundingsocieties", "-100", "Principal"),
+        ),
+        (
+            txn("2020-01-31", loan, "Service Fee", "0.20", "0"),
+            ("expenses:service", "0.20", "Service Fee"),
+        ),
+    ];
+    let config = Config::default();
+    for (txn, expected) in &cases {
+        let txns = [txn.clone()];
+        let entries = fs_ledger::entries(&txns, &config).unwrap();
+        let p = &entries[0].postings[0];
+        let posting = (&*p.account, p.amount.to_string(), p.comment);
+        assert_eq!(
+            posting,
+            (expected.0, expected.1.to_owned(), expected.2),
+            "{}",
+            txn.title
+        );
+        assert_eq!(entries[0].postings.len(), 1, "{}", txn.title);
+    }
+}
+
+#[test]
+fn separators() {
+    let config = Config {
+        decimal_sep: ",".to_owned(),
+        thousands_sep: ".".to_owned(),
+        ..Config::default()
+    };
+    let amount = |s: &str| s.parse().unwrap();
+    assert_eq!(config.amount(amount("-1234.56")), "-1.234,56");
+    let config = Config {
+        thousands_sep: String::new(),
+        ..Config::default()
+    };
+    assert_eq!(config.amount(amount("1234567.8")), "1234567.80");
+}
+
+#[test]
+fn parse_either_separator() {
+    use fs_ledger::amount::parse;
+    let amount = |s: &str| s.parse().unwrap();
+    assert_eq!(parse("1,234.56").unwrap(), amount("1234.56"));
+    assert_eq!(parse("1.234,56").unwrap(), amount("1234.56"));
+    assert_eq!(parse("0,2").unwrap(), amount("0.2"));
+    assert_eq!(parse("1,000").unwrap(), amount("1000"));
+    assert_eq!(parse("1.234.567").unwrap(), amount("1234567"));
+}
+
+#[test]
+fn beancount_narration() {
+    let txns = [txn(
+        "2020-01-02",
+        "Auto Investment: invested 100 into XXXX-00000000",
+        "",
+        "100",
+        "0",
+    )];
+    let config = Config::default();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let out = write(&entries, &mut fs_ledger::Beancount, &config);
+    assert!(out.contains("2020-01-02 * \"XXXX-00000000\" \"Auto Investment\"\n"));
+}
+
+#[test]
+fn beancount_accounts() {
+    let txns = [txn(
+        "2020-01-02",
+        "Auto Investment: invested 100 into XXXX-00000000",
+        "",
+        "100",
+        "0",
+    )];
+    let config: Config = toml::from_str(
+        r#"
+        per_loan = true
+
+        [beancount_accounts]
+        "assets:fundingsocieties" = "Assets:FundingSocieties"
+        "assets:funds" = "Assets:Invested"
+        "#,
+    )
+    .unwrap();
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let out = write(&entries, &mut fs_ledger::Beancount, &config);
+    assert!(out.starts_with(
+        "2020-01-02 open Assets:FundingSocieties\n\
+         2020-01-02 open Assets:Invested:Fundingsocieties:XXXX-00000000\n"
+    ));
+}
+
+#[test]
+fn extraction_error() {
+    use fs_ledger::LedgerError;
+    let e = LedgerError::Extraction(vec!["Syntax Error: bad".to_owned()]);
+    assert_eq!(
+        e.to_string(),
+        "pdftotext reported 1 errors, first \"Syntax Error: bad\""
+    );
+    let e = LedgerError::Extraction(Vec::new());
+    assert_eq!(e.to_string(), "pdftotext reported errors");
+}
+
+#[test]
+fn dedup() {
+    let title = "XXXX-00000000 (1 of 1 repayment)";
+    let fee = |total: &str| Transaction {
+        total: total.parse().unwrap(),
+        ..txn("2020-01-31", title, "Service Fee", "0.20", "0")
+    };
+    // the same fee twice in a statement, then again in the next one
+    let mut txns = vec![fee("99.80"), fee("99.60"), fee("99.60")];
+    fs_ledger::dedup(&mut txns);
+    let totals: Vec<_> = txns.iter().map(|txn| txn.total.to_string()).collect();
+    assert_eq!(totals, ["99.80", "99.60"]);
+}
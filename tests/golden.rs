@@ -0,0 +1,276 @@
+//! Runs pdftotext output of a statement through the whole pipeline.
+mod common;
+
+use common::{ledger, write};
+use fs_ledger::{Config, Ledger};
+use std::fs;
+
+#[test]
+fn statement() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let config = Config::default();
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    assert!(fs_ledger::verify(&statement.transactions).is_empty());
+    let expected = fs::read_to_string("tests/fixtures/statement.ledger").unwrap();
+    assert_eq!(ledger(&statement.transactions, &config), expected);
+}
+
+#[test]
+fn malformed_row() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let src = src.replace("(0.20)", "(0.2O)");
+    match fs_ledger::parse_statement(&src, &Config::default()) {
+        Err(fs_ledger::LedgerError::MalformedRow { row, .. }) => assert_eq!(row, 6),
+        other => panic!("expected malformed row, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn title_wrapped_across_lines() {
+    let src = fs::read_to_string("tests/fixtures/wrap.txt").unwrap();
+    let statement = fs_ledger::parse_statement(&src, &Config::default()).unwrap();
+    let txn = statement.transactions.last().unwrap();
+    assert_eq!(txn.title, "Adjustment for investment to XXXX-00000000");
+    assert!(fs_ledger::verify(&statement.transactions).is_empty());
+}
+
+#[test]
+fn title_wrapped_past_amounts() {
+    let src = fs::read_to_string("tests/fixtures/wrap.txt").unwrap();
+    let src = src
+        .replace(
+            "into XXXX-00000000\n\n(100.00)\n\n0.00\n\n100.00\n\n",
+            "into\n\n(100.00)\n\n0.00\n\n100.00\n\nXXXX-00000000\n",
+        )
+        .replace(
+            "Service Fee\n\n(0.20)\n\n0.00\n\n190.80\n\n",
+            "Service\n\n(0.20)\n\n0.00\n\n190.80\n\nFee\n",
+        );
+    let statement = fs_ledger::parse_statement(&src, &Config::default()).unwrap();
+    let txns = &statement.transactions;
+    assert_eq!(
+        txns[1].title,
+        "Auto Investment: invested 100 into XXXX-00000000"
+    );
+    assert_eq!(txns[5].comment, "Service Fee");
+    assert!(fs_ledger::verify(&statement.transactions).is_empty());
+}
+
+#[test]
+fn non_breaking_space() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let src = src.replace("| | Interest", "|\u{a0}|  Interest\u{a0}");
+    let statement = fs_ledger::parse_statement(&src, &Config::default()).unwrap();
+    let txn = &statement.transactions[4];
+    assert_eq!(txn.title, "XXXX-00000000 (1 of 1 repayment)");
+    assert_eq!(txn.comment, "Interest");
+}
+
+#[test]
+fn read_text_statement() {
+    let config = Config::default();
+    let statement = fs_ledger::read_statement("tests/fixtures/statement.txt", &config).unwrap();
+    assert_eq!(statement.transactions.len(), 6);
+}
+
+#[test]
+fn read_statements_in_order() {
+    let paths = [
+        "tests/fixtures/wrap.txt",
+        "tests/fixtures/statement.txt",
+        "tests/fixtures/wrap.txt",
+    ];
+    let config = Config::default();
+    let counts: Vec<_> = fs_ledger::read_statements(&paths, &config, 2)
+        .into_iter()
+        .map(|statement| statement.unwrap().transactions.len())
+        .collect();
+    assert_eq!(counts, [7, 6, 7]);
+}
+
+#[test]
+fn missing_statement() {
+    let e = fs_ledger::read_statement("tests/fixtures/missing.pdf", &Config::default())
+        .err()
+        .unwrap();
+    assert_eq!(
+        e.to_string(),
+        "tests/fixtures/missing.pdf: input file not found"
+    );
+}
+
+#[test]
+fn invested_with_separators() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let src = src.replace(
+        "invested 100 into XXXX-00000000\n\n(100.00)",
+        "invested 1,200.50 into XXXX-00000000\n\n(1,200.50)",
+    );
+    let config = Config::default();
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    let entries = fs_ledger::entries(&statement.transactions, &config).unwrap();
+    let invested = &entries[1];
+    assert_eq!(invested.payee, "XXXX-00000000");
+    assert_eq!(
+        invested.postings[0].account,
+        "assets:funds:fundingsocieties"
+    );
+    assert_eq!(invested.postings[0].amount, "1200.50".parse().unwrap());
+}
+
+#[test]
+fn empty_statement() {
+    let src = fs::read_to_string("tests/fixtures/empty.txt").unwrap();
+    let config = Config::default();
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    assert!(statement.transactions.is_empty());
+    assert!(ledger(&statement.transactions, &config).is_empty());
+}
+
+#[test]
+fn commodity_from_header() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let config = Config::default();
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    assert_eq!(statement.commodity, Some("MYR"));
+    let src = src.replace("Balance (RM)", "Balance (S$)");
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    assert_eq!(statement.commodity, Some("SGD"));
+}
+
+#[test]
+fn commodity_of_each_statement() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let sgd = src.replace("Balance (RM)", "Balance (S$)");
+    let config = Config::default();
+    let mut txns = fs_ledger::parse_statement(&src, &config)
+        .unwrap()
+        .transactions;
+    txns.extend(
+        fs_ledger::parse_statement(&sgd, &config)
+            .unwrap()
+            .transactions,
+    );
+    let entries = fs_ledger::entries(&txns, &config).unwrap();
+    let commodities: Vec<_> = entries.iter().map(|entry| entry.commodity).collect();
+    assert_eq!(commodities[..4], ["MYR"; 4]);
+    assert_eq!(commodities[4..], ["SGD"; 4]);
+    let out = write(&entries, &mut Ledger, &config);
+    assert_eq!(out.matches(" MYR  ;").count(), 6);
+    assert_eq!(out.matches(" SGD  ;").count(), 6);
+}
+
+#[test]
+fn read_ledger_back() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let config = Config::default();
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    let ledger = fs::read_to_string("tests/fixtures/statement.ledger").unwrap();
+    let txns = fs_ledger::read_ledger(&ledger, &config).unwrap();
+    assert_eq!(txns.len(), statement.transactions.len());
+    for (txn, orig) in txns.iter().zip(&statement.transactions) {
+        assert_eq!(
+            (txn.date, txn.debit, txn.credit, txn.total),
+            (orig.date, orig.debit, orig.credit, orig.total)
+        );
+    }
+    assert_eq!(txns[3].title, "XXXX-00000000");
+    assert_eq!(txns[3].comment, "Principal");
+}
+
+#[test]
+fn transaction_iter_stops_at_malformed_row() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let src = src.replace("(0.20)", "(0.2O)");
+    let iter = fs_ledger::TransactionIter::new(&src, &Config::default()).unwrap();
+    let rows: Vec<_> = iter.collect();
+    assert_eq!(rows.len(), 6);
+    assert!(rows[..5].iter().all(|row| row.is_ok()));
+    assert!(matches!(
+        rows[5],
+        Err(fs_ledger::LedgerError::MalformedRow { row: 6, .. })
+    ));
+}
+
+#[test]
+fn statement_period() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let src = src.replacen(
+        "\n\n",
+        "\n\nStatement Period: 01 Jan 2020 - 31 Jan 2020\n\n",
+        1,
+    );
+    let statement = fs_ledger::parse_statement(&src, &Config::default()).unwrap();
+    let day = |s: &str| s.parse().unwrap();
+    assert_eq!(
+        statement.period,
+        Some((day("2020-01-01"), day("2020-01-31")))
+    );
+    assert_eq!(statement.transactions.len(), 6);
+}
+
+#[test]
+fn decimal_comma() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let config = Config::default();
+    let expected = fs_ledger::parse_statement(&src, &config).unwrap();
+    let src = src
+        .replace(".00\n", ",00\n")
+        .replace("0.20", "0,20")
+        .replace("190.80", "190,80");
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    assert_eq!(statement.transactions, expected.transactions);
+}
+
+#[test]
+fn zero_variants() {
+    let src = fs::read_to_string("tests/fixtures/zero.txt").unwrap();
+    let config = Config::default();
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    assert!(fs_ledger::verify(&statement.transactions).is_empty());
+    let expected = fs::read_to_string("tests/fixtures/statement.ledger").unwrap();
+    assert_eq!(ledger(&statement.transactions, &config), expected);
+}
+
+#[test]
+fn json_rows() {
+    let src = fs::read_to_string("tests/fixtures/zero.txt").unwrap();
+    // a zero fee has nothing to post but is still a row
+    let src = src.replace("(0.20)\n\n0.00\n\n190.80", "(0.00)\n\n0.00\n\n191.00");
+    let config = Config {
+        sst_rate: Some("6".parse().unwrap()),
+        ..Config::default()
+    };
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    let entries = fs_ledger::entries(&statement.transactions, &config).unwrap();
+    let out = write(&entries, &mut fs_ledger::Json::default(), &config);
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+    assert_eq!(rows.len(), statement.transactions.len());
+    let debits: Vec<_> = rows
+        .iter()
+        .map(|row| row["debit"].as_str().unwrap())
+        .collect();
+    assert_eq!(debits, ["0.00", "100.00", "10.00", "0.00", "0.00", "0.00"]);
+    assert_eq!(rows[5]["comment"], "Service Fee");
+    assert_eq!(rows[5]["account"], "expenses:service");
+}
+
+#[test]
+fn page_comments() {
+    let src = fs::read_to_string("tests/fixtures/statement.txt").unwrap();
+    let src = src.replacen("\n2020-01-30\n", "\n\x0c2020-01-30\n", 1);
+    let config = Config {
+        page_comments: true,
+        ..Config::default()
+    };
+    let statement = fs_ledger::parse_statement(&src, &config).unwrap();
+    let pages: Vec<_> = statement
+        .transactions
+        .iter()
+        .map(|txn| txn.page.unwrap())
+        .collect();
+    assert_eq!(pages, [1, 1, 2, 2, 2, 2]);
+    let out = ledger(&statement.transactions, &config);
+    assert!(out.starts_with("; --- page 1 ---\n\n2020-01-01 "));
+    assert!(out.contains("; --- page 2 ---\n\n2020-01-30 "));
+}